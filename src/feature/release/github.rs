@@ -2,6 +2,8 @@ use crate::libs::release::{Release, ReleaseType};
 
 use log::{debug, error, info};
 
+// Only used by the cleanup in `test_create`.
+#[allow(dead_code)]
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct DeleteReleaseParams
 {
@@ -61,13 +63,14 @@ pub async fn create(args: crate::Args, release: &Release, tag_oid: &git2::Oid, r
     }
 
     let tag = repository.find_tag(*tag_oid).expect("Failed to find the tag.");
+    let tag_name = tag.name().expect("Failed to get the tag name.");
     let commit = repository.find_commit(release.commit).expect("Failed to find the commit.");
     
     let result = octocrab
         .repos(owner, repo)
         .releases()
-        .create(version.as_str())
-        .name(version.as_str())
+        .create(tag_name)
+        .name(tag_name)
         .body(tag.message().unwrap())
         .draft(false)
         .prerelease(release.tag == ReleaseType::PreRelease)
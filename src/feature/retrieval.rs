@@ -2,81 +2,461 @@ use std::collections::HashMap;
 
 use log::{debug, error, info, warn};
 
-use crate::{libs::{release::{Release, ReleaseContributor, ReleaseType}, version::{CommitType, SemanticVersion}}, SemverData};
+use crate::{libs::{classification::{self, CommitFacts}, conventional::{split_footers, ConventionalCommit}, data::SemverDataBranch, release::{Release, ReleaseContributor, ReleaseType}, requirement::VersionReq, tag_format::{TagFormat, TagFormatError}, scheme::VersioningScheme, version::{CommitType, SemanticVersion}}, SemverData};
 
-pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repository) -> Vec<Release>
+/// A tag that carries a version.
+#[derive(Clone)]
+struct VersionTag
 {
-    // Get Current Branch
-    let head = repository.head().unwrap();
-    let branch = head.shorthand().unwrap();
-    info!("Selected Branch: {}", branch);
+    name: String,
+    commit: git2::Oid,
+    version: SemanticVersion,
+}
 
-    // Get all Tags
-    let mut commit_tags = HashMap::<git2::Oid, git2::Tag>::new();
-    let tags = repository.tag_names(None).unwrap();
-    
-    // Sort Tags.
-    for tag_name in tags.iter() 
+/// Names of all tags, annotated and lightweight.
+pub fn tag_names(repository: &git2::Repository) -> Vec<String>
+{
+    repository.tag_names(None).unwrap().iter().flatten().map(|x| x.to_string()).collect()
+}
+
+/// All tags that follow the tag format and match `only_tags`.
+fn version_tags(tag_format: &TagFormat, only_tags: Option<&VersionReq>, repository: &git2::Repository) -> Vec<VersionTag>
+{
+    let mut version_tags = Vec::<VersionTag>::new();
+    for tag_name in tag_names(repository).iter()
     {
-        let obj = repository.revparse_single(tag_name.unwrap()).unwrap();
-        if let Some(tag) = obj.as_tag() 
+        // Ignore tags that are not versions, e.g. `docs-latest`.
+        let version = match tag_format.parse(tag_name)
         {
-            // Now lets get the commit for the tag
-            let commit = tag.target().unwrap().peel_to_commit().unwrap();
-            commit_tags.insert(commit.id(), tag.clone());
+            Ok(version) => version,
+            Err(error @ TagFormatError::Filtered(_)) => {
+                debug!("Ignoring Tag: {}", error);
+                continue;
+            }
+            Err(error) => {
+                warn!("Ignoring Tag: {} - {}", tag_name, error);
+                continue;
+            }
+        };
+
+        if only_tags.is_some_and(|x| !x.matches(&version))
+        {
+            debug!("Ignoring Tag: {} - does not match {}", tag_name, only_tags.unwrap());
+            continue;
         }
+
+        // Now lets get the commit for the tag; annotated tags are peeled, lightweight tags point at it directly.
+        let commit = match repository.revparse_single(format!("refs/tags/{}", tag_name).as_str()).and_then(|x| x.peel_to_commit())
+        {
+            Ok(commit) => commit.id(),
+            Err(error) => {
+                warn!("Ignoring Tag: {} - {}", tag_name, error.message());
+                continue;
+            }
+        };
+
+        version_tags.push(VersionTag { name: tag_name.to_string(), commit, version });
     }
+    version_tags
+}
 
-    // Print all Tags
-    for (commit_id, tag) in commit_tags.iter() 
+/// The highest version tagged on `tip` or any of its ancestors, which is where the next release starts from.
+fn baseline<'a>(version_tags: &'a [VersionTag], tip: git2::Oid, repository: &git2::Repository) -> Option<&'a VersionTag>
+{
+    // Highest first, so that usually only the latest tag has to be looked up in the history.
+    let mut candidates = version_tags.iter().collect::<Vec<&VersionTag>>();
+    candidates.sort_by(|left, right| right.version.cmp(&left.version));
+    candidates.into_iter().find(|x| x.commit == tip || repository.graph_descendant_of(tip, x.commit).unwrap_or(false))
+}
+
+/// Version tags of the history of `tip`, like [`version_tags`], for shallow clones too.
+///
+/// A shallow clone without a reachable version tag is an error; with `deepen`, history and tags are fetched from
+/// `origin` in growing depths instead, until a version tag is reachable or the clone is complete.
+fn complete_history(tag_format: &TagFormat, only_tags: Option<&VersionReq>, tip: git2::Oid, deepen: bool, repository: &git2::Repository) -> Result<Vec<VersionTag>, String>
+{
+    let mut depth = 64;
+    let mut fetched_all = false;
+    loop
     {
-        debug!("Tag: {} - {}", commit_id, tag.name().unwrap());
+        let tags = version_tags(tag_format, only_tags, repository);
+        if !repository.is_shallow() || baseline(&tags, tip, repository).is_some()
+        {
+            return Ok(tags);
+        }
+
+        if !deepen
+        {
+            return Err("Shallow clone without a version tag in its history, the version cannot be determined. \
+                Check out the full history, e.g. `fetch-depth: 0` for actions/checkout or `GIT_DEPTH: 0` on GitLab, or pass --deepen.".to_string());
+        }
+        if fetched_all
+        {
+            return Err("Still a shallow clone after fetching the full history from origin.".to_string());
+        }
+
+        info!("Shallow clone without a version tag, fetching {} commits of history from origin", if depth == i32::MAX { "all".to_string() } else { depth.to_string() });
+        fetch_history(depth, repository).map_err(|error| format!("Failed to deepen the shallow clone: {}", error.message()))?;
+        fetched_all = depth == i32::MAX;
+
+        // Eventually, fetch everything rather than deepening forever.
+        depth = if depth >= 1 << 20 { i32::MAX } else { depth * 8 };
     }
+}
+
+/// Fetches the branches and tags of `origin`, with `depth` commits of history.
+fn fetch_history(depth: i32, repository: &git2::Repository) -> Result<(), git2::Error>
+{
+    let mut remote = repository.find_remote("origin")?;
+
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(crate::git_credentials_callback);
+    let mut options = git2::FetchOptions::new();
+    options.remote_callbacks(callbacks)
+        .download_tags(git2::AutotagOption::All)
+        .depth(depth);
+
+    // The configured refspecs.
+    remote.fetch(&[] as &[&str], Some(&mut options), None)
+}
 
-    // Get all Commits
+/// The commit and version of `--baseline <ref>=<version>`, e.g. `a1b2c3d=2.3.0` or `legacy-final=2.3.0`.
+fn explicit_baseline(baseline: &str, scheme: &dyn VersioningScheme, repository: &git2::Repository) -> Result<VersionTag, String>
+{
+    let (reference, version) = baseline.rsplit_once('=')
+        .ok_or_else(|| format!("expected <ref>=<version>, got `{}`", baseline))?;
+    let version = scheme.parse(version)
+        .map_err(|error| format!("`{}`: {}", version, error))?;
+    let commit = revision(reference, repository)
+        .map_err(|error| format!("`{}`: {}", reference, error.message()))?;
+
+    Ok(VersionTag { name: reference.to_string(), commit, version })
+}
+
+/// The commit of a revision, e.g. `HEAD~3`, `v1.2.0` or `a1b2c3d`.
+fn revision(revision: &str, repository: &git2::Repository) -> Result<git2::Oid, git2::Error>
+{
+    repository.revparse_single(revision)
+        .and_then(|x| x.peel_to_commit())
+        .map(|x| x.id())
+}
+
+/// Commits since the baseline up to `tip`, oldest first.
+///
+/// History reachable from the baseline is hidden from the walk, so only the unreleased commits are visited
+/// no matter how long the history is.
+fn unreleased_commits(baseline: Option<git2::Oid>, tip: git2::Oid, first_parent: bool, repository: &git2::Repository) -> git2::Revwalk<'_>
+{
     let mut revwalk = repository.revwalk().unwrap();
-    revwalk.push_head().unwrap();
-    let mut commits: Vec<git2::Commit> = revwalk
-        .map(|id| repository.find_commit(id.unwrap()).unwrap())
-        .collect();
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE).unwrap();
+    revwalk.push(tip).unwrap();
+    if let Some(baseline) = baseline
+    {
+        revwalk.hide(baseline).unwrap();
+    }
+    if first_parent
+    {
+        revwalk.simplify_first_parent().unwrap();
+    }
+    revwalk
+}
 
-    // Print all Commits
-    for commit in commits.iter() 
+/// Title of the pull request of a merge commit, the first line of its body; e.g. `feat: add x` of
+/// `Merge pull request #12 from owner/branch\n\nfeat: add x`.
+fn pull_request_title<'a>(commit: &'a git2::Commit) -> Option<&'a str>
+{
+    if commit.parent_count() < 2
     {
-        debug!("Commit: {} - {}", commit.id(), commit.message().unwrap());
+        return None;
+    }
+
+    commit.body()
+        .and_then(|x| x.lines().map(|line| line.trim()).find(|line| !line.is_empty()))
+}
+
+/// Environment variables of CI systems that name the branch of a detached checkout, by priority.
+///
+/// `GITHUB_HEAD_REF` is the source branch of a pull request, where `GITHUB_REF_NAME` would be `<number>/merge`.
+const BRANCH_VARIABLES: [&str; 4] = ["GITHUB_HEAD_REF", "GITHUB_REF_NAME", "CI_COMMIT_REF_NAME", "BRANCH_NAME"];
+
+/// Lines of a squash merge body that follow the format, e.g. `* feat: a` or `- fix(ui): b`.
+fn squash_entries(body: &str) -> Vec<(ConventionalCommit, &str)>
+{
+    body.lines()
+        .map(|x| x.trim())
+        .map(|x| x.strip_prefix("* ").or_else(|| x.strip_prefix("- ")).unwrap_or(x).trim())
+        .filter_map(|x| ConventionalCommit::parse(x).ok().map(|entry| (entry, x)))
+        .collect()
+}
+
+/// The first marker found in the message, ignoring case.
+fn skip_marker<'a>(message: &str, markers: &'a [String]) -> Option<&'a str>
+{
+    let message = message.to_lowercase();
+    markers.iter().find(|x| message.contains(&x.to_lowercase())).map(|x| x.as_str())
+}
+
+/// The commit that a revert takes back, by id or by its header.
+struct RevertTarget
+{
+    id: Option<String>,
+    header: Option<String>,
+}
+
+impl RevertTarget
+{
+    fn matches(&self, id: git2::Oid, message: &str) -> bool
+    {
+        self.id.as_ref().is_some_and(|x| id.to_string().starts_with(x.as_str()))
+            || self.header.as_ref().is_some_and(|x| message.lines().next() == Some(x.as_str()))
     }
+}
 
-    commits.reverse();
+/// Recognises `git revert` messages, `Revert "<header>"` and `This reverts commit <id>.`, and `revert: <header>`.
+fn revert_target(message: &str) -> Option<RevertTarget>
+{
+    let id = message.find("This reverts commit ")
+        .map(|x| message[x + "This reverts commit ".len()..].chars().take_while(|x| x.is_ascii_hexdigit()).collect::<String>())
+        .filter(|x| x.len() >= 7);
 
-    // Cleanup commits that are within a tag.
-    let mut version = SemanticVersion::new();
+    let header = message.lines().next().unwrap_or_default();
+    let header = match header.strip_prefix("Revert \"").and_then(|x| x.strip_suffix('"'))
     {
-        let last_commit_index = {
-            let mut commit_tag_index = 0;
-            for (index, commit) in commits.iter().enumerate()
-            {
-                if commit_tags.contains_key(&commit.id()) 
-                {
-                    commit_tag_index = index + 1;
-                }
-            }
-            commit_tag_index
-        };
+        Some(reverted) => Some(reverted.to_string()),
+        None => ConventionalCommit::parse(header).ok()
+            .filter(|x| x.kind.eq_ignore_ascii_case("revert"))
+            .map(|x| x.description),
+    };
+
+    (id.is_some() || header.is_some()).then_some(RevertTarget { id, header })
+}
+
+/// The message a commit is classified by; a merge stands for its pull request, so it is the title of the pull request.
+fn classified_message<'a>(commit: &'a git2::Commit, merge_title: bool) -> &'a str
+{
+    match merge_title.then(|| pull_request_title(commit)).flatten()
+    {
+        Some(title) => title,
+        None => commit.message().unwrap_or_default(),
+    }
+}
+
+/// Commits taken back by a later revert in the same history, along with their reverts.
+///
+/// None of the commits have been released yet, so a reverted commit and its revert cancel each other out,
+/// even when the reverted commit would have made a release of its own. Each revert takes back the latest matching commit.
+fn cancelled_commits(commits: &[git2::Commit], merge_title: bool, skip_markers: &[String]) -> Vec<git2::Oid>
+{
+    let mut cancelled = Vec::<git2::Oid>::new();
+    for (index, commit) in commits.iter().enumerate()
+    {
+        if skip_marker(commit.message().unwrap_or_default(), skip_markers).is_some()
+        {
+            continue;
+        }
+        let Some(target) = revert_target(classified_message(commit, merge_title)) else { continue };
 
-        // Get the last commit that is not tagged.
-        let last_commit = commits[last_commit_index-1].clone();
-        if let Some(tag) = commit_tags.get(&last_commit.id())
+        let reverted = commits[..index].iter().rev()
+            .filter(|x| !cancelled.contains(&x.id()) && skip_marker(x.message().unwrap_or_default(), skip_markers).is_none())
+            .find(|x| target.matches(x.id(), classified_message(x, merge_title)));
+        if let Some(reverted) = reverted
         {
-            let tag_version = tag.name().unwrap();
-            debug!("Last Tag: {} - {}", last_commit.id(), tag_version);
-            version = SemanticVersion::parse(tag_version);
+            cancelled.push(reverted.id());
+            cancelled.push(commit.id());
         }
+    }
+    cancelled
+}
 
-        commits = commits[last_commit_index..].to_vec();
+/// Name of the branch that is being released.
+///
+/// The branch is taken from `--branch` or the checked out branch; CI systems usually check out a detached HEAD,
+/// whose branch is taken from the CI environment or finally the remote branch that contains HEAD, in that order.
+pub fn branch(explicit: Option<&str>, repository: &git2::Repository) -> String
+{
+    resolve_branch(explicit, &|x| std::env::var(x).ok(), repository)
+}
+
+fn resolve_branch(explicit: Option<&str>, env: &dyn Fn(&str) -> Option<String>, repository: &git2::Repository) -> String
+{
+    if let Some(branch) = explicit
+    {
+        return branch.to_string();
     }
-    let version = version; // De-mut the variable.
 
-    info!("Commits: {}", commits.len());
+    // HEAD names the branch even before its first commit.
+    if !repository.head_detached().unwrap_or(false)
+    {
+        if let Some(branch) = repository.find_reference("HEAD").ok().and_then(|x| x.symbolic_target().map(|x| x.to_string()))
+        {
+            return branch.strip_prefix("refs/heads/").unwrap_or(&branch).to_string();
+        }
+    }
+
+    // Tag pipelines name the tag instead of a branch.
+    let is_tag = |name: &str| repository.find_reference(&format!("refs/tags/{}", name)).is_ok();
+    if let Some((variable, branch)) = BRANCH_VARIABLES.iter().find_map(|x| env(x).filter(|x| !x.is_empty() && !is_tag(x)).map(|branch| (x, branch)))
+    {
+        debug!("Branch from {}: {}", variable, branch);
+        return branch;
+    }
+
+    let head = match repository.head()
+    {
+        Ok(head) => head,
+        Err(error) => {
+            warn!("Could not determine the branch, pass --branch: {}", error.message());
+            return "HEAD".to_string();
+        }
+    };
+
+    // Detached; prefer a remote branch pointing at HEAD over one that merely contains it.
+    let head = head.peel_to_commit().unwrap().id();
+    let mut candidates = repository.branches(Some(git2::BranchType::Remote)).unwrap()
+        .flatten()
+        .filter_map(|(branch, _)| Some((branch.name().ok()??.to_string(), branch.get().peel_to_commit().ok()?.id())))
+        .filter(|(name, _)| !name.ends_with("/HEAD"))
+        .filter(|(_, tip)| *tip == head || repository.graph_descendant_of(*tip, head).unwrap_or(false))
+        .collect::<Vec<(String, git2::Oid)>>();
+    candidates.sort_by_key(|(name, tip)| (*tip != head, name.clone()));
+
+    match candidates.first()
+    {
+        Some((name, _)) => {
+            debug!("Branch from remote branch containing HEAD: {}", name);
+            // Drop the remote, `origin/feature/x` is `feature/x`.
+            name.split_once('/').map_or(name.as_str(), |(_, branch)| branch).to_string()
+        }
+        None => {
+            warn!("Could not determine the branch of the detached HEAD, pass --branch");
+            "HEAD".to_string()
+        }
+    }
+}
+
+/// Rules of the first configured branch that matches the branch name.
+pub fn branch_rules<'a>(semver_data: &'a SemverData, branch: &str) -> Option<&'a SemverDataBranch>
+{
+    semver_data.branches.iter().find(
+        |x| 
+        regex::Regex::new(x.name.as_str()).unwrap().is_match(branch)
+    )
+}
+
+/// The marker, e.g. `[skip ci]`, on the analysed commit that makes the whole run a no-op.
+pub fn run_skip_marker(args: &crate::Args, semver_data: &SemverData, repository: &git2::Repository) -> Option<String>
+{
+    let tip = repository.find_commit(revision(args.to.as_deref().unwrap_or("HEAD"), repository).ok()?).ok()?;
+    skip_marker(tip.message().unwrap_or_default(), &semver_data.commits.skip_run_markers()).map(|x| x.to_string())
+}
+
+pub fn get(args: crate::Args, semver_data: &SemverData, tag_format: &TagFormat, repository: &git2::Repository) -> Vec<Release>
+{
+    // Get Current Branch
+    let branch = branch(args.branch.as_deref(), repository);
+    info!("Selected Branch: {}", branch);
+
+    let scheme = semver_data.scheme().unwrap_or_else(|error| {
+        error!("{}", error);
+        std::process::exit(1);
+    });
+    debug!("Versioning Scheme: {}", scheme.name());
+
+    let rules = semver_data.commits.rules().unwrap_or_else(|error| {
+        error!("Commit rules: {}", error);
+        std::process::exit(1);
+    });
+    let default_type = semver_data.commits.default_type().unwrap_or_else(|error| {
+        error!("Commit default: {}", error);
+        std::process::exit(1);
+    });
+
+    let only_tags = args.only_tags.as_deref().map(VersionReq::parse).transpose().unwrap_or_else(|error| {
+        error!("--only-tags: {}", error);
+        std::process::exit(1);
+    });
+
+    // Branch Rules
+    let branch_rules = branch_rules(semver_data, &branch);
+    let branch_range = branch_rules.map(|x| x.range()).transpose().unwrap_or_else(|error| {
+        error!("Branch range: {}", error);
+        std::process::exit(1);
+    }).flatten();
+
+    // The range to analyse, `--from` (exclusive) up to `--to`; by default from the baseline up to HEAD.
+    let to = revision(args.to.as_deref().unwrap_or("HEAD"), repository).unwrap_or_else(|error| {
+        error!("--to: {}", error.message());
+        std::process::exit(1);
+    });
+    let from = args.from.as_deref().map(|x| revision(x, repository)).transpose().unwrap_or_else(|error| {
+        error!("--from: {}", error.message());
+        std::process::exit(1);
+    });
+
+    if let Some(marker) = run_skip_marker(&args, semver_data, repository)
+    {
+        info!("Skipping the run: {} is marked with {}", to, marker);
+        return Vec::new();
+    }
+    let skip_commit_markers = semver_data.commits.skip_commit_markers();
+
+    // Shallow clones may be missing the history, and the tags, of the latest release.
+    let version_tags = match args.baseline
+    {
+        Some(_) => version_tags(tag_format, only_tags.as_ref(), repository),
+        None => complete_history(tag_format, only_tags.as_ref(), from.unwrap_or(to), args.deepen, repository).unwrap_or_else(|error| {
+            error!("{}", error);
+            std::process::exit(1);
+        }),
+    };
+
+    // Print all Tags
+    let mut commit_tags = HashMap::<git2::Oid, Vec<String>>::new();
+    for tag in version_tags.iter() 
+    {
+        debug!("Tag: {} - {} [{}]", tag.commit, tag.name, tag.version);
+        commit_tags.entry(tag.commit).or_default().push(tag.name.clone());
+    }
+
+    // Everything up to the highest reachable version has been released already, unless told otherwise.
+    let baseline = match args.baseline.as_deref()
+    {
+        Some(explicit) => Some(explicit_baseline(explicit, scheme.as_ref(), repository).unwrap_or_else(|error| {
+            error!("--baseline: {}", error);
+            std::process::exit(1);
+        })),
+        None => baseline(&version_tags, from.unwrap_or(to), repository).cloned(),
+    };
+    let version = match &baseline
+    {
+        Some(baseline) => {
+            info!("Baseline: {} - {}", baseline.name, baseline.commit);
+            baseline.version.clone()
+        }
+        None => SemanticVersion::new(),
+    };
+
+    // Without a previous release, the first release may take a configured version instead of a bump from 0.0.0.
+    let mut initial_version = match baseline
+    {
+        Some(_) => None,
+        None => semver_data.initial_version(scheme.as_ref()).unwrap_or_else(|error| {
+            error!("Initial version: {}", error);
+            std::process::exit(1);
+        }),
+    };
+
+    let first_parent = branch_rules.and_then(|x| x.first_parent).unwrap_or(false);
+    let merge_title = branch_rules.and_then(|x| x.merge_title).unwrap_or(false);
+    let squash = semver_data.commits.squash.unwrap_or(false);
+    let list_none = semver_data.commits.list_none.unwrap_or(false);
+    let start = from.or(baseline.as_ref().map(|x| x.commit));
+    let commits = unreleased_commits(start, to, first_parent, repository)
+        .map(|id| repository.find_commit(id.unwrap()).unwrap())
+        .collect::<Vec<git2::Commit>>();
+    let cancelled = cancelled_commits(&commits, merge_title, &skip_commit_markers);
+    let mut commits = commits.into_iter().peekable();
+    let mut commit_count = 0;
 
     // Store Data about the current Version Release.
     //  Later on, we will catchup with the commits and create a new release.
@@ -86,20 +466,30 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
     let mut release_majors = Vec::<String>::new();
     let mut release_minors = Vec::<String>::new();
     let mut release_patches = Vec::<String>::new();
+    let mut release_reverts = Vec::<String>::new();
+    let mut release_others = Vec::<String>::new();
+    let mut release_rejected = Vec::<String>::new();
+    let mut release_skipped = Vec::<String>::new();
     let mut release_contributors = Vec::<ReleaseContributor>::new();
 
     // Parse each commit and fill out information that is needed.
-    for commit in commits.iter() 
+    while let Some(commit) = commits.next()
     {
+        debug!("Commit: {} - {}", commit.id(), commit.message().unwrap());
+        commit_count += 1;
+
         let mut release_type;
         let should_major_release;
         let should_minor_release;
-        if commits.last().unwrap().id() == commit.id()
+        let graduate;
+        if commits.peek().is_none()
         {
             should_major_release = args.keep_major_up_to_date && semver_data.branches.iter().any(|x| x.name == branch && x.increment.is_some() && x.increment.clone().unwrap().contains(&"MAJOR".to_string()));
             should_minor_release = args.keep_minor_up_to_date && semver_data.branches.iter().any(|x| x.name == branch && x.increment.is_some() && x.increment.clone().unwrap().contains(&"MINOR".to_string()));
+            // Leave the initial development (0.y.z) behind, regardless of the commit type.
+            graduate = args.graduate && release_version.get_major() == 0;
 
-            if args.force_release || (should_major_release || should_minor_release)
+            if args.force_release || (should_major_release || should_minor_release) || graduate
             {
                 release_type = ReleaseType::Release;
             }
@@ -116,150 +506,210 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
         {
             should_major_release = false;
             should_minor_release = false;
+            graduate = false;
 
             release_type = ReleaseType::None;
         }
 
         let commit_id = commit.id();
-        let commit_message = commit.message().unwrap();
+        let commit_message = classified_message(&commit, merge_title);
         let commit_author = commit.author();
+        let commit_time = commit.time().seconds() + i64::from(commit.time().offset_minutes()) * 60;
 
-        // Check if the commit is tagged
-        let tag: Option<git2::Tag> = if commit_tags.contains_key(&commit_id) 
-        {
-            let tags = commit_tags.clone();
-            let tag = tags.get(&commit_id).unwrap();
-            Some(tag.clone())
-        } 
-        else 
-        {
-            None
-        };
-
-        // Do not continue, if the commit is tagged.
-        if tag.is_some() 
+        // Check if the commit is tagged; tags below the baseline, e.g. of merged maintenance branches, are part of the next release.
+        let tag = commit_tags.get(&commit_id).map(|x| x.join(", "));
+        if let Some(tag) = &tag 
         {
-            warn!("Commit: [TAGGED: {}] {} - {} - {}", tag.unwrap().name().unwrap(), commit_id, commit_author.name().unwrap(), commit_message);
-            break;
+            warn!("Commit: [TAGGED: {}] {} - {} - {}", tag, commit_id, commit_author.name().unwrap(), commit_message);
         }
 
-        // First word of the commit message
-        let regex_str = regex::Regex::new(r#"^([a-zA-Z]+\s*)+(\([a-zA-Z]+\)|)(!?):"#).unwrap();
-        // Check if the commit message follows the format.
-        let captures = regex_str.captures(commit_message);
-        
-        let is_major;
-        let follows_format: bool;
-        let first_word;
-        if let Some(captures) = captures
+        // Marked commits, e.g. `[skip release]`, are left out altogether.
+        if let Some(marker) = skip_marker(commit.message().unwrap_or_default(), &skip_commit_markers)
         {
-            
-            first_word = captures.get(0).unwrap().as_str();
-            follows_format = true;
-
-            is_major = captures.len() > 3 && captures.get(3).unwrap().as_str() == "!";
+            info!("Commit: [SKIPPED: {}] {} - {} - {}", marker, commit_id, commit_author.name().unwrap(), commit_message);
+            release_skipped.push(format!("{} (skipped by {})", commit_message.lines().next().unwrap_or_default(), marker));
+            continue;
         }
-        else
+
+        // A revert and the commit it reverts cancel each other out, when neither has been released.
+        if cancelled.contains(&commit_id)
         {
-            first_word = commit_message.split_whitespace().next().unwrap();
-            follows_format = false;
-            is_major = false;
+            info!("Commit: [REVERTED] {} - {} - {}", commit_id, commit_author.name().unwrap(), commit_message);
+            continue;
         }
+        let revert = revert_target(commit_message);
 
-        // Check if the first word is in the map
-        let mut skip = false;
-        let mut commit_type : CommitType = CommitType::Patch;
-        for (key, value) in semver_data.commits.map.iter() 
+        // Squash merges list the commits of the pull request in the body, whether or not its title follows the format.
+        let entries = if squash { squash_entries(split_footers(commit_message.split_once('\n').map_or("", |(_, body)| body)).0) } else { Vec::new() };
+
+        // Check if the commit message follows the format; reverts made by git and squash merges of formatted commits are fine as they are.
+        let conventional = ConventionalCommit::parse(commit_message);
+        if let (Err(error), None, true) = (&conventional, &revert, entries.is_empty())
         {
-            for value in value.iter() 
+            if args.skip_non_formatted
             {
-                if (semver_data.commits.case_sensitive && first_word == value) || (!semver_data.commits.case_sensitive && first_word.contains(value)) 
-                {
-                    // Parse the Key to the Commit Type, default is PATCH.
-                    commit_type = match key.to_uppercase().as_str() 
-                    {
-                        "MAJOR" => CommitType::Major,
-                        "MINOR" => CommitType::Minor,
-                        "PATCH" => CommitType::Patch,
-                        _ => match semver_data.commits.default.to_uppercase().as_str() 
-                        {
-                            "MAJOR" => CommitType::Major,
-                            "MINOR" => CommitType::Minor,
-                            "PATCH" => CommitType::Patch,
-                            _ => CommitType::Patch,
-                        }
-                    };
-                    break;
-                }
+                warn!("Commit: [NON-FORMATTED] {} - {} - {} ({})", commit_id, commit_author.name().unwrap(), commit_message, error);
+                continue;
             }
 
-            // Check if the commit message follows the format.
-            if !follows_format
+            error!("Commit: [ERROR: NON-FORMATTED] {} - {} - {} ({})", commit_id, commit_author.name().unwrap(), commit_message, error);
+            if args.exit_on_error
             {
-                if args.skip_non_formatted
-                {
-                    warn!("Commit: [NON-FORMATTED] {} - {} - {}", commit_id, commit_author.name().unwrap(), commit_message);
-                    skip = true;
-                    break;
-                }
-                else
-                {
-                    error!("Commit: [ERROR: NON-FORMATTED] {} - {} - {}", commit_id, commit_author.name().unwrap(), commit_message);
-                    if args.exit_on_error
-                    {
-                        std::process::exit(1);
-                    }
-                }
+                std::process::exit(1);
             }
         }
 
-        if skip
+        // The type of the commit, or the first word of commits that do not follow the format.
+        let first_word = match &conventional
         {
-            continue;
-        }
+            Ok(conventional) => conventional.kind.as_str(),
+            Err(_) => commit_message.split_whitespace().next().unwrap_or_default(),
+        };
+        let scope = conventional.as_ref().ok().and_then(|x| x.scope.as_deref());
+        let matches = |word: &str, value: &str| if semver_data.commits.case_sensitive { word == value } else { word.eq_ignore_ascii_case(value) };
 
-        if is_major
+        // Check the classification rules, by priority.
+        let facts = CommitFacts {
+            kind: first_word,
+            subject: commit_message.lines().next().unwrap_or_default(),
+            body: commit_message.split_once('\n').map_or("", |(_, body)| body.trim()),
+            footers: conventional.as_ref().map(|x| x.footers.as_slice()).unwrap_or_default(),
+        };
+        let mut commit_type = classification::classify(&rules, &facts).unwrap_or(default_type);
+
+        // `!` or a `BREAKING CHANGE` footer.
+        if conventional.as_ref().is_ok_and(|x| x.breaking)
         {
             commit_type = CommitType::Major;
         }
 
+        // Each commit of a squash merge is a change of its own.
+        // The change is the commit message without its footers, normalized when it follows the format.
+        let message = match &conventional
+        {
+            Ok(conventional) => ConventionalCommit { footers: vec![], ..conventional.clone() }.to_string(),
+            Err(_) => commit_message.trim().to_string(),
+        };
+        let mut changes = vec![(commit_type, message)];
+        if !entries.is_empty()
+        {
+            // The title of the pull request stands on its own, unless it repeats one of its commits or does not follow the format.
+            changes = vec![(commit_type, commit_message.lines().next().unwrap_or_default().to_string())];
+            if conventional.is_err()
+            {
+                commit_type = CommitType::None;
+            }
+            if conventional.is_err() || entries.iter().any(|(entry, _)| conventional.as_ref().is_ok_and(|x| x.kind == entry.kind && x.description == entry.description))
+            {
+                changes.clear();
+            }
+            for (entry, line) in entries.into_iter()
+            {
+                let facts = CommitFacts { kind: &entry.kind, subject: line, body: "", footers: &[] };
+                let entry_type = if entry.breaking { CommitType::Major } else { classification::classify(&rules, &facts).unwrap_or(default_type) };
+                if entry_type.rank() < commit_type.rank()
+                {
+                    commit_type = entry_type;
+                }
+                changes.push((entry_type, line.to_string()));
+            }
+        }
+
+        // Commits of the NONE level never release on their own; a forced release bumps them as a patch.
+        let bumps = commit_type != CommitType::None;
+        let increment_type = if bumps { commit_type } else { CommitType::Patch };
+        let always_increment = args.always_increment && bumps;
+
         // Trigger Release.
-        if semver_data.commits.release.iter().any(|x| first_word.contains(format!("({})", x).as_str())) || commit_type == CommitType::Major
+        if bumps && (semver_data.commits.release.iter().any(|x| scope.is_some_and(|scope| matches(scope, x))) || commit_type == CommitType::Major)
         {
             release_type = ReleaseType::Release;
         }
         // Trigger Prerelease.
-        if semver_data.commits.prerelease.iter().any(|x| first_word.contains(format!("({})", x).as_str()))
+        if bumps && semver_data.commits.prerelease.iter().any(|x| scope.is_some_and(|scope| matches(scope, x)))
         {
             release_type = ReleaseType::PreRelease;
         }
 
-        // Place Commit Messages into the correct array.
-        let branch_rules = semver_data.branches.iter().find(
-            |x| 
-            regex::Regex::new(x.name.as_str()).unwrap().is_match(branch)
-        );
+        // `Release-As: x.y.z` pins the version of the release made by this commit, as long as it goes forward.
+        let mut release_as = conventional.as_ref().ok()
+            .and_then(|x| x.footers.iter().find(|x| x.token.eq_ignore_ascii_case("Release-As")))
+            .and_then(|footer| {
+                let released = current_release.as_ref().map_or(&version, |x| &x.version);
+                let error = match scheme.parse(&footer.value)
+                {
+                    Ok(pinned) if pinned > *released => return Some(pinned),
+                    Ok(pinned) => format!("{} is not higher than {}", pinned, released),
+                    Err(error) => format!("`{}`: {}", footer.value, error),
+                };
+
+                error!("Commit: [ERROR: RELEASE-AS] {} - {}", commit_id, error);
+                release_rejected.push(format!("{} ({})", error, commit_message.lines().next().unwrap_or_default()));
+                // Dry-runs report the rejection with the release instead.
+                if args.exit_on_error && !args.dry_run
+                {
+                    std::process::exit(1);
+                }
+                None
+            });
+        if release_as.is_some() && release_type == ReleaseType::None
+        {
+            release_type = ReleaseType::Release;
+        }
 
         let mut can_increment = release_type != ReleaseType::None;
         // Check the branch rules, and verify that we can increment in the way we like to.
-        if branch_rules.is_some()
+        if let Some(branch_rules) = branch_rules
         {
-            let branch_rules = branch_rules.unwrap();
             if can_increment && branch_rules.prerelease.is_some() && branch_rules.prerelease.unwrap() {
                 release_type = ReleaseType::PreRelease;
             }
 
-            if branch_rules.increment.is_some()
+            if let Some(increment) = &branch_rules.increment
+            {
+                // Only limits the commits that release, in any case, e.g. `"increment": ["MINOR"]`.
+                can_increment = can_increment && increment.iter().any(|x| x.eq_ignore_ascii_case(format!("{:?}", increment_type).as_str()));
+            }
+        }
+
+        if graduate || release_as.is_some()
+        {
+            can_increment = true;
+        }
+
+        // Maintenance branches must not leave their range, e.g. a `~1.4` branch cannot release 1.5.0.
+        if let Some(branch_range) = branch_range.as_ref().filter(|_| can_increment)
+        {
+            let mut next_version = release_as.clone().or_else(|| initial_version.clone()).unwrap_or_else(|| release_version.clone());
+            if release_as.is_none() && initial_version.is_none()
             {
-                can_increment = branch_rules.increment.clone().unwrap().contains(&format!("{:?}", commit_type).to_string());
+                scheme.increment(&mut next_version, &increment_type, commit_time);
+            }
+            let next_version = SemanticVersion::from(next_version.get_major(), next_version.get_minor(), next_version.get_patch());
+            if !branch_range.matches(&next_version)
+            {
+                error!("Commit: [ERROR: OUT OF RANGE] {} - {} is outside of the branch range {}", commit_id, next_version, branch_range);
+                if args.exit_on_error
+                {
+                    std::process::exit(1);
+                }
+                can_increment = false;
+                release_as = None;
             }
         }
 
-        match commit_type 
+        // Place Commit Messages into the correct array; reverts of earlier releases are listed on their own.
+        for (change_type, change) in changes.iter()
         {
-            CommitType::Major => release_majors.push(commit_message.to_string()),
-            CommitType::Minor => release_minors.push(commit_message.to_string()),
-            CommitType::Patch => release_patches.push(commit_message.to_string()),
+            match change_type 
+            {
+                _ if revert.is_some() => release_reverts.push(change.clone()),
+                CommitType::Major => release_majors.push(change.clone()),
+                CommitType::Minor => release_minors.push(change.clone()),
+                CommitType::Patch => release_patches.push(change.clone()),
+                CommitType::None if list_none => release_others.push(change.clone()),
+                CommitType::None => {}
+            }
         }
 
         let bad_emails = ["noreply."];
@@ -274,9 +724,26 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
             }
         }
         
-        if can_increment || args.always_increment
+        if let Some(pinned) = release_as.as_ref()
         {
-            release_version.increment(&commit_type);
+            info!("Release-As: {} -> {}", release_version, pinned);
+            release_version = pinned.clone();
+            initial_version = None;
+        }
+        else if graduate
+        {
+            info!("Graduating: {} -> 1.0.0", release_version);
+            release_version = SemanticVersion::from(1, 0, 0);
+            initial_version = None;
+        }
+        else if let Some(initial_version) = can_increment.then(|| initial_version.take()).flatten()
+        {
+            // The first release takes the initial version, whatever `--always-increment` counted up before it.
+            release_version = initial_version;
+        }
+        else if can_increment || always_increment
+        {
+            scheme.increment(&mut release_version, &increment_type, commit_time);
         }
 
         if should_major_release
@@ -286,9 +753,14 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
                 commit: commit_id,
                 tag: ReleaseType::Release, 
                 version: SemanticVersion::from(release_version.get_major(), u32::MAX, u32::MAX),
+                pinned: false,
+                rejected: release_rejected.clone(),
                 majors: release_majors.clone(), 
                 minors: release_minors.clone(), 
                 patches: release_patches.clone(), 
+                reverts: release_reverts.clone(), 
+                others: release_others.clone(), 
+                skipped: release_skipped.clone(),
                 contributors: release_contributors.clone() 
             });
         }
@@ -300,9 +772,14 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
                 commit: commit_id,
                 tag: ReleaseType::Release, 
                 version: SemanticVersion::from(release_version.get_major(), release_version.get_minor(), u32::MAX),
+                pinned: false,
+                rejected: release_rejected.clone(),
                 majors: release_majors.clone(), 
                 minors: release_minors.clone(), 
                 patches: release_patches.clone(), 
+                reverts: release_reverts.clone(), 
+                others: release_others.clone(), 
+                skipped: release_skipped.clone(),
                 contributors: release_contributors.clone() 
             });
         }
@@ -317,15 +794,32 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
             }
 
 
+            // Pre-releases count up on their channel instead of taking a new version, e.g. 1.3.0-rc.1, 1.3.0-rc.2, ...
+            if release_type == ReleaseType::PreRelease
+            {
+                let channel = branch_rules.and_then(|x| x.prerelease_identifier.as_deref()).unwrap_or("rc");
+                let existing = version_tags.iter().map(|x| &x.version)
+                    .chain(releases.iter().map(|x| &x.version))
+                    .chain(current_release.iter().map(|x| &x.version))
+                    .cloned()
+                    .collect::<Vec<SemanticVersion>>();
+                release_version = release_version.next_pre_release(channel, &existing);
+            }
+
             // Create a new release.
             //  Piece together the release data to catchup.
             let release = Release { 
                 commit: commit_id,
                 tag: release_type, 
                 version: release_version.clone(), 
+                pinned: release_as.is_some(),
+                rejected: release_rejected.clone(),
                 majors: release_majors.clone(), 
                 minors: release_minors.clone(), 
                 patches: release_patches.clone(), 
+                reverts: release_reverts.clone(), 
+                others: release_others.clone(), 
+                skipped: release_skipped.clone(),
                 contributors: release_contributors.clone() 
             };
 
@@ -333,35 +827,81 @@ pub fn get(args: crate::Args, semver_data: &SemverData, repository: &git2::Repos
             release_majors.clear();
             release_minors.clear();
             release_patches.clear();
+            release_reverts.clear();
+            release_others.clear();
+            release_rejected.clear();
+            release_skipped.clear();
             release_contributors.clear();
             
             debug!("Switching Releases:\n\tOld - {:?}\n\tNew - {:?}", current_release, release.clone());
             debug!("Delta Version: {}.{}.{}", release_version.get_delta_major(), release_version.get_delta_minor(), release_version.get_delta_patch());
-            release_version = SemanticVersion::from(release_version.get_major(), release_version.get_minor(), release_version.get_patch());
+            release_version.reset_delta();
             current_release = Some(release);
         }
 
         info!(
             "Commit: [{:?}] {}{}{} - {} - {}",
             commit_type, 
-            if tag.is_some() { format!("[TAGGED: {}] ", tag.unwrap().name().unwrap()) } else { "".to_string() }, 
+            if let Some(tag) = tag { format!("[TAGGED: {}] ", tag) } else { "".to_string() }, 
             if can_increment { "[TAGGING] ".to_string() } else { "".to_string() }, 
             commit_id, 
             commit_author.name().unwrap(), 
             commit_message
         );
     }
-    
 
-    // Close the last release.
-    if current_release.is_some()
+    info!("Commits: {}", commit_count);
+
+    // Close the last release; commits skipped after it are reported on it, or on a pending entry when nothing releases.
+    match current_release
     {
-        releases.push(current_release.clone().unwrap());
+        Some(mut release) => {
+            release.skipped.append(&mut release_skipped);
+            releases.push(release);
+        }
+        None if !release_skipped.is_empty() => releases.push(Release {
+            commit: to,
+            tag: ReleaseType::None,
+            version,
+            pinned: false,
+            rejected: vec![],
+            majors: vec![],
+            minors: vec![],
+            patches: vec![],
+            reverts: vec![],
+            others: vec![],
+            skipped: release_skipped,
+            contributors: vec![],
+        }),
+        None => {}
     }
 
     releases
 }
 
+#[test]
+fn test_branch_increment()
+{
+    let test_repo = TestRepo::new("branch-increment");
+    let first = test_repo.commit("chore: init");
+    test_repo.tag("1.0.0", first);
+    for message in ["feat: a", "fix(release): b", "feat(release): c"]
+    {
+        test_repo.commit(message);
+    }
+
+    let semver_data: SemverData = serde_json::from_value(serde_json::json!({
+        "branches": [{ "name": ".*", "increment": ["minor"] }],
+        "commits": { "default": "PATCH", "caseSensitive": false, "release": ["release"], "prerelease": [], "map": { "MINOR": ["feat"] } },
+        "tagging": { "supported_repositories": {} }
+    })).unwrap();
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+
+    // `feat: a` does not release, and `fix(release): b` is not allowed to.
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].version.to_string(), "1.1.0");
+    assert_eq!(releases[0].patches, ["fix(release): b"]);
+}
 #[test]
 fn test_get()
 {
@@ -384,15 +924,25 @@ fn test_get()
             map: Default::default(),
             release: vec![],
             prerelease: vec![],
+            rules: None,
+            squash: None,
+            list_none: None,
+            skip: None,
         
         },
         tagging: crate::SemverDataTagging {
             supported_repositories: Default::default(),
+            tag_format: None,
+            on_conflict: None,
+            include: None,
+            exclude: None,
         },
+        versioning: None,
+        initial_version: None,
     };
     let repository = git2::Repository::open(".").unwrap();
 
-    let releases = get(args, &semver_data, &repository);
+    let releases = plan(args, &semver_data, &repository);
 
     if !releases.is_empty()
     {
@@ -404,3 +954,580 @@ fn test_get()
         }
     }
 }
+
+#[test]
+fn test_baseline()
+{
+    let test_repo = TestRepo::new("baseline");
+    let repository = &test_repo.repository;
+    let first = test_repo.commit("feat: first");
+    let second = test_repo.commit("feat: second");
+    let third = test_repo.commit("fix: third");
+    let unrelated = test_repo.commit_on("feat: unrelated", &[first], None);
+
+    // Annotated and lightweight tags, several on one commit, one on a commit that HEAD does not contain.
+    test_repo.tag("v1.0.0", first);
+    repository.tag_lightweight("v1.2.0", &repository.find_object(second, None).unwrap(), false).unwrap();
+    repository.tag_lightweight("v1.1.0", &repository.find_object(second, None).unwrap(), false).unwrap();
+    repository.tag_lightweight("docs-latest", &repository.find_object(third, None).unwrap(), false).unwrap();
+    test_repo.tag("v9.0.0", unrelated);
+
+    let tag_format = TagFormat::new("v{version}").unwrap();
+    let tags = version_tags(&tag_format, None, repository);
+    assert_eq!(tags.len(), 4);
+
+    let latest = baseline(&tags, third, repository).unwrap();
+    assert_eq!((latest.name.as_str(), latest.commit), ("v1.2.0", second));
+
+    let only_tags = VersionReq::parse("~1.1").unwrap();
+    let tags = version_tags(&tag_format, Some(&only_tags), repository);
+    assert_eq!(baseline(&tags, third, repository).unwrap().name, "v1.1.0");
+
+    let tag_format = TagFormat::new("v{version}").unwrap().with_filters(None, Some(r"^v1\.2\.")).unwrap();
+    let tags = version_tags(&tag_format, None, repository);
+    assert_eq!(baseline(&tags, third, repository).unwrap().name, "v1.1.0");
+}
+
+#[test]
+fn test_many_tags()
+{
+    let test_repo = TestRepo::new("many_tags");
+    let repository = &test_repo.repository;
+    for index in 0..200
+    {
+        let commit = test_repo.commit(&format!("fix: {}", index));
+        repository.tag_lightweight(&format!("v1.0.{}", index), &repository.find_object(commit, None).unwrap(), false).unwrap();
+    }
+    let tip = test_repo.commit("feat!: search");
+    let unrelated = test_repo.commit_on("feat!: unrelated", &[tip], None);
+    test_repo.tag("v9.0.0", unrelated);
+
+    // The highest version in the history of HEAD is the baseline, the unrelated one is not.
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &test_semver_data(), repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["2.0.0"]);
+}
+
+#[test]
+fn test_changes()
+{
+    let test_repo = TestRepo::new("changes");
+    test_repo.commit("feat!: api\n");
+    test_repo.commit("Update README\n\n");
+    test_repo.commit("fix(release): retry\n\nThe request is sent again.\n\nRefs #12\nReviewed-by: Name\n");
+
+    let mut semver_data = test_semver_data();
+    semver_data.commits.release = vec!["release".to_string()];
+
+    // Changes are listed without trailing line breaks or footers.
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.len(), 2);
+    assert_eq!(releases[0].majors, ["feat!: api"]);
+    assert_eq!(releases[1].patches, ["Update README", "fix(release): retry\n\nThe request is sent again."]);
+
+    let json = serde_json::to_value(&releases[1]).unwrap();
+    assert_eq!(json["changes"]["patch"], serde_json::json!(["Update README", "fix(release): retry\n\nThe request is sent again."]));
+}
+
+#[test]
+fn test_merge_history()
+{
+    let test_repo = TestRepo::new("merges");
+    let first = test_repo.commit("fix: first");
+    let feature = test_repo.commit_on("fix: feature work", &[first], None);
+    let feature = test_repo.commit_on("fix: more feature work", &[feature], None);
+    test_repo.commit_on("Merge pull request #1 from owner/feature\n\nfeat!: feature", &[first, feature], Some("HEAD"));
+
+    let releases = |first_parent: bool, merge_title: bool| {
+        let semver_data = SemverData {
+            branches: vec![SemverDataBranch {
+                name: ".*".to_string(),
+                prerelease: None,
+                prerelease_identifier: None,
+                range: None,
+                increment: None,
+                first_parent: Some(first_parent),
+                merge_title: Some(merge_title),
+            }],
+            ..test_semver_data()
+        };
+        plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository)
+    };
+
+    let merges = releases(true, true);
+    assert_eq!(merges.len(), 1);
+    assert_eq!(merges[0].majors, ["feat!: feature"]);
+    assert_eq!(merges[0].patches, ["fix: first"]);
+
+    let all = releases(false, true);
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].patches, ["fix: first", "fix: feature work", "fix: more feature work"]);
+
+    // Without the title, the merge is just another non-conventional commit.
+    assert!(releases(true, false).is_empty());
+}
+
+#[test]
+fn test_unreleased_commits()
+{
+    let test_repo = TestRepo::new("unreleased");
+    let commits = (0..500).map(|index| test_repo.commit(&format!("fix: {}", index))).collect::<Vec<git2::Oid>>();
+    let repository = &test_repo.repository;
+
+    // Only the commits after the baseline are visited, oldest first.
+    let unreleased = unreleased_commits(Some(commits[494]), commits[499], false, repository).map(|x| x.unwrap()).collect::<Vec<git2::Oid>>();
+    assert_eq!(unreleased, commits[495..]);
+    assert_eq!(unreleased_commits(None, commits[499], false, repository).count(), 500);
+    assert_eq!(unreleased_commits(Some(commits[499]), commits[499], false, repository).count(), 0);
+
+    // The walk never reads the released history, however long it is.
+    for commit in commits[..400].iter()
+    {
+        let hex = commit.to_string();
+        std::fs::remove_file(test_repo.path.join(".git").join("objects").join(&hex[..2]).join(&hex[2..])).unwrap();
+    }
+    let repository = git2::Repository::open(&test_repo.path).unwrap();
+    let unreleased = unreleased_commits(Some(commits[494]), commits[499], false, &repository).collect::<Result<Vec<git2::Oid>, git2::Error>>();
+    assert_eq!(unreleased.unwrap(), commits[495..]);
+    assert!(unreleased_commits(None, commits[499], false, &repository).any(|x| x.is_err()));
+}
+
+#[test]
+fn test_initial_version()
+{
+    let test_repo = TestRepo::new("initial");
+    let repository = &test_repo.repository;
+    let first = test_repo.commit("fix: first");
+    test_repo.commit("feat!: second");
+    test_repo.commit("feat!: third");
+
+    let versions = |semver_data: &SemverData, baseline: Option<&str>| {
+        let args = crate::Args { dry_run: true, baseline: baseline.map(|x| x.to_string()), ..Default::default() };
+        plan(args, semver_data, repository).iter().map(|x| x.version.to_string()).collect::<Vec<String>>()
+    };
+
+    let semver_data = SemverData { initial_version: Some("1.0.0".to_string()), ..test_semver_data() };
+    assert_eq!(versions(&semver_data, None), ["1.0.0", "2.0.0"]);
+
+    // Commits incremented before the first release leave the initial version to it.
+    let args = crate::Args { dry_run: true, always_increment: true, ..Default::default() };
+    assert_eq!(plan(args, &semver_data, repository).iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["1.0.0", "2.0.0"]);
+
+    // An explicit baseline wins over the initial version.
+    assert_eq!(versions(&semver_data, Some("HEAD~1=2.3.0")), ["3.0.0"]);
+    assert_eq!(versions(&semver_data, Some(&format!("{}=2.3.0", first))), ["3.0.0", "4.0.0"]);
+
+    assert!(explicit_baseline("HEAD", &crate::libs::scheme::SemVer::default(), repository).is_err());
+    assert!(explicit_baseline("HEAD=two", &crate::libs::scheme::SemVer::default(), repository).is_err());
+    assert!(explicit_baseline("missing=2.3.0", &crate::libs::scheme::SemVer::default(), repository).is_err());
+}
+
+#[test]
+fn test_range()
+{
+    let test_repo = TestRepo::new("range");
+    test_repo.commit("fix: first");
+    test_repo.commit("feat!: second");
+    test_repo.commit("feat!: third");
+
+    // Any range, without checking it out.
+    let versions_between = |from: Option<&str>, to: Option<&str>| {
+        let args = crate::Args { dry_run: true, from: from.map(|x| x.to_string()), to: to.map(|x| x.to_string()), ..Default::default() };
+        plan(args, &test_semver_data(), &test_repo.repository).iter().map(|x| (x.version.to_string(), x.majors.clone())).collect::<Vec<(String, Vec<String>)>>()
+    };
+    assert_eq!(versions_between(None, Some("HEAD~1")), [("0.1.0".to_string(), vec!["feat!: second".to_string()])]);
+    assert_eq!(versions_between(Some("HEAD~1"), None), [("0.1.0".to_string(), vec!["feat!: third".to_string()])]);
+    assert!(versions_between(Some("HEAD~1"), Some("HEAD~1")).is_empty());
+
+    // Between two historic tags, the releases of the range are tagged already.
+    let test_repo = TestRepo::new("range-tagged");
+    test_repo.tag("1.0.0", test_repo.commit("feat!: first"));
+    test_repo.commit("fix: second");
+    test_repo.tag("1.1.0", test_repo.commit("feat(release): third"));
+    test_repo.commit("feat(release): fourth");
+    let mut semver_data = test_semver_data();
+    semver_data.commits.map.insert("MINOR".to_string(), vec!["feat".to_string()]);
+    semver_data.commits.release.push("release".to_string());
+    let args = crate::Args { dry_run: true, from: Some("1.0.0".to_string()), to: Some("1.1.0".to_string()), ..Default::default() };
+    let releases = plan(args.clone(), &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["1.1.0"]);
+    assert_eq!(releases[0].patches, ["fix: second"]);
+    assert_eq!(releases[0].minors, ["feat(release): third"]);
+    assert!(!crate::feature::tagging::checks_tags(&args));
+    assert!(crate::feature::tagging::checks_tags(&crate::Args { dry_run: true, ..Default::default() }));
+}
+
+#[test]
+fn test_graduate()
+{
+    use crate::libs::data::SemverDataVersioning;
+
+    let test_repo = TestRepo::new("graduate");
+    test_repo.commit("fix: first");
+    test_repo.commit("feat!: second");
+    test_repo.commit("feat!: third");
+
+    let versions = |semver_data: &SemverData, graduate: bool| {
+        let args = crate::Args { dry_run: true, graduate, ..Default::default() };
+        plan(args, semver_data, &test_repo.repository).iter().map(|x| x.version.to_string()).collect::<Vec<String>>()
+    };
+
+    // Breaking changes only bump the minor version during initial development.
+    assert_eq!(versions(&test_semver_data(), false), ["0.1.0", "0.2.0"]);
+    // Graduating releases 1.0.0 at the last commit.
+    assert_eq!(versions(&test_semver_data(), true), ["0.1.0", "1.0.0"]);
+
+    let semver_data = SemverData {
+        versioning: Some(SemverDataVersioning { scheme: None, format: None, initial_development: Some(false) }),
+        ..test_semver_data()
+    };
+    assert_eq!(versions(&semver_data, false), ["1.0.0", "2.0.0"]);
+}
+
+/// A repository in a temporary directory, which is removed again when dropped.
+#[cfg(test)]
+pub struct TestRepo
+{
+    pub path: std::path::PathBuf,
+    pub repository: git2::Repository,
+    time: std::cell::Cell<i64>,
+}
+
+#[cfg(test)]
+impl TestRepo
+{
+    pub fn new(name: &str) -> TestRepo
+    {
+        let path = std::env::temp_dir().join(format!("flexvers-{}-{}", name, rand::random::<u32>()));
+        let repository = git2::Repository::init(&path).unwrap();
+        TestRepo { path, repository, time: std::cell::Cell::new(1_700_000_000) }
+    }
+
+    /// Commits on top of HEAD, if there is one.
+    pub fn commit(&self, message: &str) -> git2::Oid
+    {
+        let head = self.repository.head().ok().and_then(|x| x.target());
+        self.commit_on(message, &head.into_iter().collect::<Vec<git2::Oid>>(), Some("HEAD"))
+    }
+
+    /// Commits with the given parents, a minute after the previous commit, and updates `reference`.
+    pub fn commit_on(&self, message: &str, parents: &[git2::Oid], reference: Option<&str>) -> git2::Oid
+    {
+        self.time.set(self.time.get() + 60);
+        let signature = git2::Signature::new("Name", "test@email.com", &git2::Time::new(self.time.get(), 0)).unwrap();
+        let tree = self.repository.find_tree(self.repository.index().unwrap().write_tree().unwrap()).unwrap();
+        let parents = parents.iter().map(|x| self.repository.find_commit(*x).unwrap()).collect::<Vec<git2::Commit>>();
+        self.repository.commit(reference, &signature, &signature, message, &tree, &parents.iter().collect::<Vec<&git2::Commit>>()).unwrap()
+    }
+
+    /// Adds an annotated tag.
+    pub fn tag(&self, name: &str, commit: git2::Oid)
+    {
+        let signature = git2::Signature::now("Name", "test@email.com").unwrap();
+        self.repository.tag(name, &self.repository.find_object(commit, None).unwrap(), &signature, name, false).unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestRepo
+{
+    fn drop(&mut self)
+    {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Releases of the repository, with the tag format of the configuration.
+#[cfg(test)]
+fn plan(args: crate::Args, semver_data: &SemverData, repository: &git2::Repository) -> Vec<Release>
+{
+    let tag_format = semver_data.tagging.tag_format(semver_data.scheme().unwrap(), &tag_names(repository)).unwrap();
+    get(args, semver_data, &tag_format, repository)
+}
+
+#[cfg(test)]
+fn test_semver_data() -> SemverData
+{
+    SemverData {
+        branches: vec![],
+        commits: crate::SemverDataCommits {
+            case_sensitive: false,
+            default: "PATCH".to_string(),
+            map: Default::default(),
+            release: vec![],
+            prerelease: vec![],
+            rules: None,
+            squash: None,
+            list_none: None,
+            skip: None,
+        },
+        tagging: crate::SemverDataTagging {
+            supported_repositories: Default::default(),
+            tag_format: None,
+            on_conflict: None,
+            include: None,
+            exclude: None,
+        },
+        versioning: None,
+        initial_version: None,
+    }
+}
+
+
+#[test]
+fn test_branch()
+{
+    let test_repo = TestRepo::new("branch");
+    let repository = &test_repo.repository;
+    let first = test_repo.commit_on("fix: first", &[], Some("refs/heads/develop"));
+    let second = test_repo.commit_on("fix: second", &[first], Some("refs/heads/develop"));
+    repository.set_head("refs/heads/develop").unwrap();
+
+    let no_env = |_: &str| None;
+    assert_eq!(resolve_branch(None, &no_env, repository), "develop");
+    assert_eq!(resolve_branch(Some("release/1.x"), &no_env, repository), "release/1.x");
+
+    // Pull requests on GitHub name the source branch in GITHUB_HEAD_REF and `<number>/merge` in GITHUB_REF_NAME.
+    let github = |x: &str| match x
+    {
+        "GITHUB_HEAD_REF" => Some("feature/x".to_string()),
+        "GITHUB_REF_NAME" => Some("12/merge".to_string()),
+        _ => None,
+    };
+    let gitlab = |x: &str| (x == "CI_COMMIT_REF_NAME").then(|| "main".to_string());
+    let empty = |x: &str| (x == "GITHUB_HEAD_REF").then(String::new);
+    let tag = |x: &str| (x == "GITHUB_REF_NAME").then(|| "1.0.0".to_string());
+
+    // A checked out branch wins over the environment.
+    assert_eq!(resolve_branch(None, &github, repository), "develop");
+
+    // Detached checkouts take the branch from the environment.
+    repository.set_head_detached(second).unwrap();
+    assert_eq!(resolve_branch(None, &github, repository), "feature/x");
+    assert_eq!(resolve_branch(None, &gitlab, repository), "main");
+    assert_eq!(resolve_branch(None, &empty, repository), "HEAD");
+
+    // Then from the remote branches containing HEAD, also when the environment names a tag.
+    repository.reference("refs/remotes/origin/main", second, false, "").unwrap();
+    repository.reference("refs/remotes/origin/HEAD", second, false, "").unwrap();
+    repository.reference("refs/remotes/origin/feature/y", first, false, "").unwrap();
+    test_repo.tag("1.0.0", second);
+    assert_eq!(resolve_branch(None, &tag, repository), "main");
+    repository.set_head_detached(first).unwrap();
+    assert_eq!(resolve_branch(None, &no_env, repository), "feature/y");
+
+    // A new repository is on its branch before the first commit.
+    let unborn = TestRepo::new("branch-unborn");
+    unborn.repository.set_head("refs/heads/trunk").unwrap();
+    assert_eq!(resolve_branch(None, &github, &unborn.repository), "trunk");
+}
+
+#[test]
+fn test_shallow()
+{
+    let test_repo = TestRepo::new("shallow");
+    let first = test_repo.commit("fix: first");
+    let second = test_repo.commit("fix: second");
+    test_repo.repository.tag_lightweight("1.0.0", &test_repo.repository.find_object(first, None).unwrap(), false).unwrap();
+    let tag_format = TagFormat::new(TagFormat::DEFAULT).unwrap();
+
+    // A depth 1 clone of the second commit; libgit2 cannot clone shallow locally.
+    std::fs::write(test_repo.path.join(".git").join("shallow"), format!("{}\n", second)).unwrap();
+    let repository = git2::Repository::open(&test_repo.path).unwrap();
+    assert!(repository.is_shallow());
+
+    let error = complete_history(&tag_format, None, second, false, &repository).err().unwrap();
+    assert!(error.contains("fetch-depth: 0"), "{}", error);
+    // There is no origin to deepen from.
+    assert!(complete_history(&tag_format, None, second, true, &repository).is_err());
+
+    // The tag is part of the history of the first commit, regardless.
+    assert_eq!(complete_history(&tag_format, None, first, false, &repository).unwrap().len(), 1);
+
+    std::fs::remove_file(test_repo.path.join(".git").join("shallow")).unwrap();
+    let repository = git2::Repository::open(&test_repo.path).unwrap();
+    assert_eq!(complete_history(&tag_format, None, second, false, &repository).unwrap().len(), 1);
+}
+
+#[test]
+fn test_classification()
+{
+    let test_repo = TestRepo::new("classification");
+    for message in ["Feat(api-v2): new endpoint", "fix(core/net): retry", "chore: drop v1\n\nBREAKING CHANGE: the v1 endpoints are gone", "docs(release): notes"]
+    {
+        test_repo.commit(message);
+    }
+
+    let mut semver_data = test_semver_data();
+    semver_data.commits.map.insert("MINOR".to_string(), vec!["feat".to_string()]);
+    semver_data.commits.release.push("release".to_string());
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+
+    assert_eq!(releases.len(), 2);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0", "0.1.1"]);
+    assert_eq!(releases[0].minors, ["Feat(api-v2): new endpoint"]);
+    assert_eq!(releases[0].majors, ["chore!: drop v1"]);
+    assert_eq!(releases[1].patches, ["docs(release): notes"]);
+
+    // Commits that no rule matches take the default.
+    semver_data.commits.default = "MINOR".to_string();
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases[0].minors, ["Feat(api-v2): new endpoint", "fix(core/net): retry"]);
+    assert_eq!(releases[1].minors, ["docs(release): notes"]);
+}
+
+#[test]
+fn test_release_as()
+{
+    let test_repo = TestRepo::new("release-as");
+    for message in ["feat!: first", "chore: marketing\n\nRelease-As: 3.0.0", "fix: second", "fix!: third\n\nRelease-As: 2.0.0"]
+    {
+        test_repo.commit(message);
+    }
+
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &test_semver_data(), &test_repo.repository);
+    let versions = releases.iter().map(|x| (x.version.to_string(), x.pinned)).collect::<Vec<(String, bool)>>();
+
+    // Going backwards is refused, and the version is computed instead.
+    assert_eq!(versions, [("0.1.0".to_string(), false), ("3.0.0".to_string(), true), ("4.0.0".to_string(), false)]);
+    assert_eq!(releases[1].patches, ["chore: marketing"]);
+    assert_eq!(releases[2].rejected, ["2.0.0 is not higher than 3.0.0 (fix!: third)"]);
+    assert!(releases[1].rejected.is_empty());
+}
+
+#[test]
+fn test_reverts()
+{
+    use crate::libs::data::SemverDataVersioning;
+
+    let test_repo = TestRepo::new("reverts");
+    test_repo.commit("feat!: first");
+    let search = test_repo.commit("feat: search");
+    test_repo.commit("fix: typo");
+    test_repo.commit(&format!("Revert \"feat: search\"\n\nThis reverts commit {}.", search));
+    test_repo.commit("revert: feat!: first");
+    let last = test_repo.commit("fix(release): last");
+
+    let mut semver_data = test_semver_data();
+    semver_data.commits.map.insert("MINOR".to_string(), vec!["feat".to_string()]);
+    semver_data.commits.release.push("release".to_string());
+    semver_data.versioning = Some(SemverDataVersioning { scheme: None, format: None, initial_development: Some(false) });
+    let releases = plan(crate::Args { dry_run: true, always_increment: true, ..Default::default() }, &semver_data, &test_repo.repository);
+
+    // The breaking change would have released on its own, but neither it nor the feature was released before.
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.0.2"]);
+    assert!(releases[0].majors.is_empty() && releases[0].minors.is_empty());
+    assert_eq!(releases[0].patches, ["fix: typo", "fix(release): last"]);
+    assert!(releases[0].reverts.is_empty());
+
+    // Reverts of released commits are listed on their own.
+    test_repo.tag("0.0.2", last);
+    test_repo.commit("revert: fix: typo");
+    test_repo.commit("fix(release): again");
+    let releases = plan(crate::Args { dry_run: true, always_increment: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.0.4"]);
+    assert_eq!(releases[0].reverts, ["revert: fix: typo"]);
+    assert_eq!(releases[0].patches, ["fix(release): again"]);
+}
+
+#[test]
+fn test_squash()
+{
+    let test_repo = TestRepo::new("squash");
+    test_repo.commit("feat(search): search (#12)\n\n* feat: index\n\n* fix: typo\n\n* feat!: drop v1\n\nCo-authored-by: Name <test@email.com>");
+
+    let mut semver_data = test_semver_data();
+    semver_data.commits.map.insert("MINOR".to_string(), vec!["feat".to_string()]);
+    assert!(plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository).is_empty());
+
+    semver_data.commits.squash = Some(true);
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].majors, ["feat!: drop v1"]);
+    assert_eq!(releases[0].minors, ["feat(search): search (#12)", "feat: index"]);
+    assert_eq!(releases[0].patches, ["fix: typo"]);
+
+    // The commits are read even when the title does not follow the format.
+    let test_repo = TestRepo::new("squash_title");
+    test_repo.commit("Add search (#12)\n\n* feat!: drop v1\n* feat: index\n\nCo-authored-by: Name <test@email.com>");
+    for skip_non_formatted in [false, true]
+    {
+        let releases = plan(crate::Args { dry_run: true, skip_non_formatted, exit_on_error: true, ..Default::default() }, &semver_data, &test_repo.repository);
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].majors, ["feat!: drop v1"]);
+        assert_eq!(releases[0].minors, ["feat: index"]);
+        assert!(releases[0].patches.is_empty());
+    }
+}
+
+#[test]
+fn test_none()
+{
+    let test_repo = TestRepo::new("none");
+    test_repo.commit("feat!: api");
+    test_repo.commit("docs(release): guide");
+
+    let mut semver_data = test_semver_data();
+    semver_data.commits.release = vec!["release".to_string()];
+    semver_data.commits.map.insert("NONE".to_string(), vec!["docs".to_string()]);
+
+    // The release scope does not release commits that do not bump.
+    let releases = plan(crate::Args { dry_run: true, always_increment: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0"]);
+
+    // Forced releases still go forward, and list them when told to.
+    let releases = plan(crate::Args { dry_run: true, force_release: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0", "0.1.1"]);
+    assert!(releases[1].patches.is_empty() && releases[1].others.is_empty());
+
+    semver_data.commits.list_none = Some(true);
+    let releases = plan(crate::Args { dry_run: true, force_release: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases[1].others, ["docs(release): guide"]);
+
+    // A NONE default keeps commits that no rule matches from releasing.
+    test_repo.commit("refactor(release): tidy");
+    semver_data.commits.list_none = None;
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0", "0.1.1"]);
+    semver_data.commits.default = "NONE".to_string();
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0"]);
+}
+
+#[test]
+fn test_skip()
+{
+    let test_repo = TestRepo::new("skip");
+    for message in ["fix: a", "feat!: b [Skip Release]", "fix!: c\n\nRelease: skip", "fix: d"]
+    {
+        test_repo.commit(message);
+    }
+
+    let releases = plan(crate::Args { dry_run: true, force_release: true, ..Default::default() }, &test_semver_data(), &test_repo.repository);
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].version.to_string(), "0.0.1");
+    assert_eq!(releases[0].patches, ["fix: a", "fix: d"]);
+    assert_eq!(releases[0].skipped, ["feat!: b [Skip Release] (skipped by [skip release])", "fix!: c (skipped by Release: skip)"]);
+
+    test_repo.commit("chore: bump [skip ci]");
+    let args = crate::Args { dry_run: true, force_release: true, ..Default::default() };
+    assert_eq!(run_skip_marker(&args, &test_semver_data(), &test_repo.repository).as_deref(), Some("[skip ci]"));
+    assert!(plan(args, &test_semver_data(), &test_repo.repository).is_empty());
+
+    // Commits skipped after the last release are reported on it.
+    test_repo.commit("fix: e [skip release]");
+    let releases = plan(crate::Args { dry_run: true, force_release: true, ..Default::default() }, &test_semver_data(), &test_repo.repository);
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].skipped.last().map(String::as_str), Some("fix: e [skip release] (skipped by [skip release])"));
+
+    // Without a release, they are reported on a pending entry that is never tagged.
+    let releases = plan(crate::Args { dry_run: true, ..Default::default() }, &test_semver_data(), &test_repo.repository);
+    assert_eq!(releases.len(), 1);
+    assert_eq!((&releases[0].tag, releases[0].version.to_string()), (&ReleaseType::None, "0.0.0".to_string()));
+    assert_eq!(releases[0].skipped.len(), 3);
+
+    // Configured markers replace the defaults.
+    let mut semver_data = test_semver_data();
+    semver_data.commits.skip = Some(crate::libs::data::SemverDataSkip { commit: Some(vec![]), run: Some(vec!["[hold]".to_string()]) });
+    let releases = plan(crate::Args { dry_run: true, force_release: true, ..Default::default() }, &semver_data, &test_repo.repository);
+    assert_eq!(releases.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["0.1.0", "0.2.0", "0.2.1"]);
+}
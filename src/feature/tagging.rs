@@ -1,15 +1,158 @@
-use log::{debug, info, error};
+use std::fmt::Display;
 
-use crate::libs::release::{Release, ReleaseType};
+use log::{debug, info, error, warn};
 
-pub fn tag(args: crate::Args, release: &Release, commit: &git2::Commit, repository: &git2::Repository) -> Option<git2::Oid>
+use crate::libs::{conventional::ConventionalCommit, data::OnConflict, release::{Release, ReleaseType}, requirement::VersionReq, tag_format::TagFormat, version::SemanticVersion};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagConflict
+{
+    /// The tag name is already taken, locally or on `origin`.
+    Exists { tag: String, remote: bool },
+    /// The version is not higher than the highest existing version.
+    Regression { tag: String, highest: String },
+}
+
+impl Display for TagConflict
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            TagConflict::Exists { tag, remote } => write!(f, "{} already exists {}", tag, if *remote { "on origin" } else { "locally" }),
+            TagConflict::Regression { tag, highest } => write!(f, "{} is not higher than the existing {}", tag, highest),
+        }
+    }
+}
+
+/// Whether the planned releases are checked against the existing tags; dry-runs over a range, e.g. between two
+/// historic tags, only report the releases of the range, which are usually tagged already.
+pub fn checks_tags(args: &crate::Args) -> bool
+{
+    !(args.dry_run && (args.from.is_some() || args.to.is_some()))
+}
+
+/// Checks the planned releases against all local and remote tags before anything is tagged.
+///
+/// Releases that would reuse a tag name or go backwards are conflicts; with [`OnConflict::Skip`] they are
+/// dropped from the plan, with [`OnConflict::Abort`] all conflicts are returned instead.
+/// The floating `x` and `x.y` tags of `keep_*_up_to_date` are expected to move and are not checked.
+/// Tags on `origin` are only checked with `check_remote`, e.g. not for dry-runs, and an unreachable remote is a warning.
+pub fn preflight(releases: Vec<Release>, tag_format: &TagFormat, range: Option<&VersionReq>, on_conflict: OnConflict, check_remote: bool, repository: &git2::Repository) -> Result<Vec<Release>, Vec<TagConflict>>
+{
+    let mut existing_tags = repository.tag_names(None).unwrap().iter()
+        .flatten()
+        .map(|x| (x.to_string(), false))
+        .collect::<Vec<(String, bool)>>();
+    if check_remote
+    {
+        match remote_tags(repository)
+        {
+            Ok(tags) => existing_tags.extend(tags.into_iter().map(|x| (x, true))),
+            Err(error) => warn!("Failed to list the tags on origin, only local tags are checked: {}", error.message()),
+        }
+    }
+
+    // Only compare against versions of the same line, e.g. a `~1.4` maintenance branch may release below 2.0.0.
+    let mut highest = existing_tags.iter()
+        .filter_map(|(tag, _)| tag_format.parse(tag).ok().map(|version| (tag.clone(), version)))
+        .filter(|(_, version)| range.is_none_or(|range| range.matches(&SemanticVersion::from(version.get_major(), version.get_minor(), version.get_patch()))))
+        .max_by(|(_, left), (_, right)| left.cmp(right));
+
+    let mut conflicts = Vec::<TagConflict>::new();
+    let mut planned = Vec::<Release>::new();
+    for release in releases.into_iter()
+    {
+        let tag = tag_format.render(&release.version);
+        if release.tag == ReleaseType::None || release.version.get_minor() == u32::MAX || release.version.get_patch() == u32::MAX
+        {
+            planned.push(release);
+            continue;
+        }
+
+        let mut release_conflicts = Vec::<TagConflict>::new();
+        // The same tag may exist locally and remotely, which is reported once.
+        if let Some((_, remote)) = existing_tags.iter().find(|(name, _)| *name == tag)
+        {
+            release_conflicts.push(TagConflict::Exists { tag: tag.clone(), remote: *remote });
+        }
+        else if let Some((highest_tag, highest_version)) = highest.as_ref().filter(|(_, version)| release.version <= *version)
+        {
+            release_conflicts.push(TagConflict::Regression { tag: tag.clone(), highest: highest_tag.clone() });
+            debug!("Regression: {} <= {}", release.version, highest_version);
+        }
+
+        if release_conflicts.is_empty()
+        {
+            // Later releases of the same plan must go past this one.
+            existing_tags.push((tag.clone(), false));
+            highest = Some((tag, release.version.clone()));
+            planned.push(release);
+        }
+        else
+        {
+            for conflict in release_conflicts.iter()
+            {
+                warn!("Tag Conflict: {}", conflict);
+            }
+            conflicts.extend(release_conflicts);
+        }
+    }
+
+    if !conflicts.is_empty() && on_conflict == OnConflict::Abort
+    {
+        return Err(conflicts);
+    }
+
+    Ok(planned)
+}
+
+/// Tag names on `origin`, or none when there is no `origin`.
+fn remote_tags(repository: &git2::Repository) -> Result<Vec<String>, git2::Error>
+{
+    let mut remote = match repository.find_remote("origin")
+    {
+        Ok(remote) => remote,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(crate::git_credentials_callback);
+
+    let connection = remote.connect_auth(git2::Direction::Fetch, Some(callbacks), None)?;
+    let tags = connection.list()?.iter()
+        .filter_map(|x| x.name().strip_prefix("refs/tags/"))
+        // Peeled annotated tags are listed a second time as `name^{}`.
+        .filter(|x| !x.ends_with("^{}"))
+        .map(|x| x.to_string())
+        .collect();
+    Ok(tags)
+}
+
+/// A release note entry for a commit message, normalized and without its footers when it follows Conventional Commits;
+/// further lines are indented to stay within the list item.
+fn note(message: &str) -> String
+{
+    let message = match ConventionalCommit::parse(message)
+    {
+        Ok(commit) => ConventionalCommit { footers: vec![], ..commit }.to_string(),
+        Err(_) => message.trim().to_string(),
+    };
+    message.lines()
+        .map(|x| if x.is_empty() { String::new() } else { format!("  {}", x) })
+        .collect::<Vec<String>>()
+        .join("\n")
+        .trim_start()
+        .to_string()
+}
+
+pub fn tag(args: crate::Args, release: &Release, tag_format: &TagFormat, commit: &git2::Commit, repository: &git2::Repository) -> Option<git2::Oid>
 {
     let app_name = std::env::var("CARGO_PKG_NAME").unwrap();
     let app_repository_url = std::env::var("CARGO_PKG_REPOSITORY").unwrap();
     let commit_author = commit.author();
 
     // Tag the commit
-    let tag_name = release.version.to_string();
+    let tag_name = tag_format.render(&release.version);
     // Build the tag message
     let mut tag_message = String::new();
     {
@@ -21,7 +164,7 @@ pub fn tag(args: crate::Args, release: &Release, commit: &git2::Commit, reposito
             tag_message.push_str("## Major Changes:\n");
             for patch in release.majors.iter() 
             {
-                tag_message.push_str(format!("* {}\n", patch).as_str());
+                tag_message.push_str(format!("* {}\n", note(patch)).as_str());
             }
             tag_message.push('\n');
         }
@@ -31,7 +174,7 @@ pub fn tag(args: crate::Args, release: &Release, commit: &git2::Commit, reposito
             tag_message.push_str("## Minor Changes:\n");
             for minor in release.minors.iter() 
             {
-                tag_message.push_str(format!("* {}\n", minor).as_str());
+                tag_message.push_str(format!("* {}\n", note(minor)).as_str());
             }
             tag_message.push('\n');
         }
@@ -41,7 +184,27 @@ pub fn tag(args: crate::Args, release: &Release, commit: &git2::Commit, reposito
             tag_message.push_str("## Patch Changes:\n");
             for major in release.patches.iter() 
             {
-                tag_message.push_str(format!("* {}\n", major).as_str());
+                tag_message.push_str(format!("* {}\n", note(major)).as_str());
+            }
+            tag_message.push('\n');
+        }
+
+        if !release.reverts.is_empty() 
+        {
+            tag_message.push_str("## Reverts:\n");
+            for revert in release.reverts.iter() 
+            {
+                tag_message.push_str(format!("* {}\n", note(revert)).as_str());
+            }
+            tag_message.push('\n');
+        }
+
+        if !release.others.is_empty() 
+        {
+            tag_message.push_str("## Other Changes:\n");
+            for other in release.others.iter() 
+            {
+                tag_message.push_str(format!("* {}\n", note(other)).as_str());
             }
             tag_message.push('\n');
         }
@@ -69,7 +232,20 @@ pub fn tag(args: crate::Args, release: &Release, commit: &git2::Commit, reposito
 
     debug!("Tagging: {} for {:?}", tag_name.as_str(), commit);
 
-    let tag_oid = repository.tag(tag_name.as_str(), commit.as_object(), &commit_author, tag_message.as_str(), true).unwrap();
+    // Only the floating x and x.y tags move, everything else was checked by `preflight` and must not be overwritten.
+    let force = release.version.get_minor() == u32::MAX || release.version.get_patch() == u32::MAX;
+    let tag_oid = match repository.tag(tag_name.as_str(), commit.as_object(), &commit_author, tag_message.as_str(), force)
+    {
+        Ok(tag_oid) => tag_oid,
+        Err(error) => {
+            error!("Failed to create Tag: {} for {}\n\t{}", tag_name.as_str(), commit.id(), error.message());
+            if args.exit_on_error
+            {
+                std::process::exit(1);
+            }
+            return None;
+        }
+    };
 
     // Callbacks
     let mut callbacks = git2::RemoteCallbacks::new();
@@ -158,4 +334,67 @@ fn test_tagging()
     // {
     //     info!("Pushed Tag: {} for {}", tag_name.as_str(), commit.id());
     // }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_note()
+{
+    assert_eq!(note("fix(core):  retry  "), "fix(core): retry");
+    assert_eq!(note("feat!: drop v1\n\nThe v1 endpoints\nare gone."), "feat!: drop v1\n\n  The v1 endpoints\n  are gone.");
+    assert_eq!(note("Update README\n\nTypos."), "Update README\n\n  Typos.");
+    assert_eq!(note("chore: marketing\n\nRelease-As: 3.0.0"), "chore: marketing");
+    assert_eq!(note("chore: drop v1\n\nBody.\n\nBREAKING CHANGE: gone\nRefs #12"), "chore!: drop v1\n\n  Body.");
+}
+
+#[test]
+fn test_preflight()
+{
+    use crate::{feature::retrieval::TestRepo, libs::release::ReleaseContributor};
+
+    let test_repo = TestRepo::new("preflight");
+    let repository = &test_repo.repository;
+    let commit = test_repo.commit("feat: initial");
+    test_repo.tag("v1.2.0", commit);
+
+    let release = |version: &str| Release {
+        commit,
+        tag: ReleaseType::Release,
+        version: SemanticVersion::parse(version).unwrap(),
+        pinned: false,
+        rejected: vec![],
+        majors: vec![],
+        minors: vec![],
+        patches: vec![],
+        reverts: vec![],
+        others: vec![],
+        skipped: vec![],
+        contributors: vec![ReleaseContributor { name: "Name".to_string(), email: "test@email.com".to_string() }],
+    };
+    let releases = vec![release("1.2.0"), release("1.1.0"), release("1.3.0"), release("1.3.0"), release("1.3.0-rc.1"), release("1.4.0")];
+    let tag_format = TagFormat::new("v{version}").unwrap();
+
+    let planned = preflight(releases.clone(), &tag_format, None, OnConflict::Skip, false, repository).unwrap();
+    assert_eq!(planned.iter().map(|x| x.version.to_string()).collect::<Vec<String>>(), ["1.3.0", "1.4.0"]);
+
+    let conflicts = preflight(releases.clone(), &tag_format, None, OnConflict::Abort, false, repository).unwrap_err();
+    assert_eq!(conflicts, [
+        TagConflict::Exists { tag: "v1.2.0".to_string(), remote: false },
+        TagConflict::Regression { tag: "v1.1.0".to_string(), highest: "v1.2.0".to_string() },
+        TagConflict::Exists { tag: "v1.3.0".to_string(), remote: false },
+        TagConflict::Regression { tag: "v1.3.0-rc.1".to_string(), highest: "v1.3.0".to_string() },
+    ]);
+
+    // Maintenance branches only compare against their own range.
+    let range = VersionReq::parse("~1.1").unwrap();
+    let planned = preflight(vec![release("1.1.1")], &tag_format, Some(&range), OnConflict::Abort, false, repository).unwrap();
+    assert_eq!(planned.len(), 1);
+
+    // Pending entries are never tagged, so they never conflict.
+    let pending = Release { tag: ReleaseType::None, ..release("1.2.0") };
+    assert_eq!(preflight(vec![pending], &tag_format, None, OnConflict::Abort, false, repository).unwrap().len(), 1);
+
+    // An unreachable origin only leaves the local tags to check.
+    repository.remote("origin", test_repo.path.join("missing").to_str().unwrap()).unwrap();
+    let planned = preflight(vec![release("1.3.0")], &tag_format, None, OnConflict::Abort, true, repository).unwrap();
+    assert_eq!(planned.len(), 1);
+}
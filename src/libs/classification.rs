@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use super::{conventional::Footer, version::CommitType};
+
+/// A rule that gives commits a commit type; commits match when they match every condition of the rule.
+///
+/// Rules are tried by priority, the highest first, and in their configured order within the same priority.
+/// The first matching rule decides, so the same history is always classified the same way.
+#[derive(Debug, Clone)]
+pub struct CommitRule
+{
+    pub commit_type: CommitType,
+    /// The conventional type, e.g. `feat`, or the first word of commits that do not follow the format.
+    kind: Option<String>,
+    /// Regex on the first line of the message.
+    subject: Option<regex::Regex>,
+    /// Regex on everything after the first line.
+    body: Option<regex::Regex>,
+    /// Token of a footer the commit must have, e.g. `BREAKING CHANGE` or `Security`.
+    footer: Option<String>,
+    priority: i32,
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleError
+{
+    /// The increment is not `MAJOR`, `MINOR`, `PATCH` or `NONE`.
+    InvalidIncrement(String),
+    InvalidRegex(String),
+    /// The rule has no conditions and would match everything.
+    Empty(usize),
+}
+
+impl Display for RuleError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            RuleError::InvalidIncrement(increment) => write!(f, "unknown increment `{}`, expected MAJOR, MINOR, PATCH or NONE", increment),
+            RuleError::InvalidRegex(reason) => write!(f, "invalid rule regex: {}", reason),
+            RuleError::Empty(index) => write!(f, "rule {} has no conditions", index),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// What rules look at of a commit.
+pub struct CommitFacts<'a>
+{
+    pub kind: &'a str,
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub footers: &'a [Footer],
+}
+
+impl CommitRule
+{
+    pub fn new(increment: &str, kind: Option<&str>, subject: Option<&str>, body: Option<&str>, footer: Option<&str>, priority: i32, case_sensitive: bool) -> Result<CommitRule, RuleError>
+    {
+        let commit_type = parse_increment(increment)?;
+        let regex = |pattern: &str| regex::Regex::new(pattern)
+            .map_err(|error| RuleError::InvalidRegex(error.to_string()));
+
+        Ok(CommitRule {
+            commit_type,
+            kind: kind.map(|x| x.to_string()),
+            subject: subject.map(regex).transpose()?,
+            body: body.map(regex).transpose()?,
+            footer: footer.map(|x| x.to_string()),
+            priority,
+            case_sensitive,
+        })
+    }
+
+    fn matches(&self, commit: &CommitFacts) -> bool
+    {
+        let equals = |left: &str, right: &str| if self.case_sensitive { left == right } else { left.eq_ignore_ascii_case(right) };
+
+        self.kind.as_deref().is_none_or(|x| equals(x, commit.kind))
+            && self.subject.as_ref().is_none_or(|x| x.is_match(commit.subject))
+            && self.body.as_ref().is_none_or(|x| x.is_match(commit.body))
+            && self.footer.as_deref().is_none_or(|x| commit.footers.iter().any(|footer| equals(x, &footer.token)))
+    }
+
+    fn is_empty(&self) -> bool
+    {
+        self.kind.is_none() && self.subject.is_none() && self.body.is_none() && self.footer.is_none()
+    }
+}
+
+/// Commit type of an increment, `MAJOR`, `MINOR`, `PATCH` or `NONE` in any case.
+pub fn parse_increment(increment: &str) -> Result<CommitType, RuleError>
+{
+    match increment.to_uppercase().as_str()
+    {
+        "MAJOR" => Ok(CommitType::Major),
+        "MINOR" => Ok(CommitType::Minor),
+        "PATCH" => Ok(CommitType::Patch),
+        "NONE" => Ok(CommitType::None),
+        _ => Err(RuleError::InvalidIncrement(increment.to_string())),
+    }
+}
+
+/// Orders the rules by priority and checks that each of them has a condition.
+pub fn prioritize(mut rules: Vec<CommitRule>) -> Result<Vec<CommitRule>, RuleError>
+{
+    if let Some(index) = rules.iter().position(|x| x.is_empty())
+    {
+        return Err(RuleError::Empty(index));
+    }
+
+    // Stable, so rules of the same priority keep their order.
+    rules.sort_by_key(|x| std::cmp::Reverse(x.priority));
+    Ok(rules)
+}
+
+/// Commit type of the first matching rule, if any.
+pub fn classify(rules: &[CommitRule], commit: &CommitFacts) -> Option<CommitType>
+{
+    rules.iter().find(|x| x.matches(commit)).map(|x| x.commit_type)
+}
+
+#[test]
+fn test_classify()
+{
+    let rules = prioritize(vec![
+        CommitRule::new("PATCH", Some("fix"), None, None, None, 0, false).unwrap(),
+        CommitRule::new("MINOR", Some("feat"), None, None, None, 0, false).unwrap(),
+        CommitRule::new("MAJOR", None, None, None, Some("BREAKING CHANGE"), 10, true).unwrap(),
+        CommitRule::new("MINOR", Some("fix"), Some(r"\(security\)"), None, None, 5, false).unwrap(),
+        CommitRule::new("MINOR", None, None, Some(r"(?i)new endpoint"), None, 0, false).unwrap(),
+        CommitRule::new("NONE", Some("docs"), None, None, None, 0, false).unwrap(),
+    ]).unwrap();
+
+    let footers = [Footer { token: "BREAKING CHANGE".to_string(), value: "gone".to_string() }];
+    let facts = |kind, subject, body, footers| CommitFacts { kind, subject, body, footers };
+    assert_eq!(classify(&rules, &facts("Fix", "Fix: typo", "", &[])), Some(CommitType::Patch));
+    assert_eq!(classify(&rules, &facts("prefix", "prefix: typo", "", &[])), None);
+    assert_eq!(classify(&rules, &facts("fix", "fix(security): escape", "", &[])), Some(CommitType::Minor));
+    assert_eq!(classify(&rules, &facts("fix", "fix: x", "", &footers)), Some(CommitType::Major));
+    assert_eq!(classify(&rules, &facts("chore", "chore: x", "Adds a New Endpoint.", &[])), Some(CommitType::Minor));
+    assert_eq!(classify(&rules, &facts("docs", "docs: x", "", &[])), Some(CommitType::None));
+
+    assert!(matches!(CommitRule::new("HUGE", Some("feat"), None, None, None, 0, false), Err(RuleError::InvalidIncrement(_))));
+    assert!(matches!(CommitRule::new("MINOR", None, Some("(unclosed"), None, None, 0, false), Err(RuleError::InvalidRegex(_))));
+    assert!(matches!(prioritize(vec![CommitRule::new("MINOR", None, None, None, None, 0, false).unwrap()]), Err(RuleError::Empty(0))));
+}
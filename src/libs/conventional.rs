@@ -0,0 +1,304 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A commit message following <https://www.conventionalcommits.org/en/v1.0.0/>.
+///
+/// ```text
+/// feat(api-v2)!: drop the v1 endpoints      <- type, scope, `!` and description
+///
+/// The v1 endpoints were deprecated a year   <- body, any number of paragraphs
+/// ago.
+///
+/// BREAKING CHANGE: clients need v2          <- footers, the last paragraph
+/// Refs #133
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit
+{
+    /// The type, e.g. `feat` or `fix`; `type` is a keyword.
+    pub kind: String,
+    pub scope: Option<String>,
+    /// Marked with `!` or a `BREAKING CHANGE` footer.
+    pub breaking: bool,
+    pub description: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+}
+
+/// A git trailer style footer, `token: value` or `token #value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer
+{
+    pub token: String,
+    /// Multi-line values keep their line breaks; `#value` keeps the `#`.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConventionalCommitError
+{
+    Empty,
+    /// The header does not start with a type, e.g. `Update README`.
+    MissingType(String),
+    /// The scope is empty or not closed, e.g. `feat(): x` or `feat(api: x`.
+    InvalidScope(String),
+    /// The type is not followed by `: `, e.g. `feat - x`.
+    MissingSeparator(String),
+    MissingDescription(String),
+}
+
+impl Display for ConventionalCommitError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            ConventionalCommitError::Empty => write!(f, "empty commit message"),
+            ConventionalCommitError::MissingType(header) => write!(f, "`{}` does not start with a type", header),
+            ConventionalCommitError::InvalidScope(header) => write!(f, "`{}` has an empty or unclosed scope", header),
+            ConventionalCommitError::MissingSeparator(header) => write!(f, "`{}` is missing `: ` after the type", header),
+            ConventionalCommitError::MissingDescription(header) => write!(f, "`{}` is missing a description", header),
+        }
+    }
+}
+
+impl std::error::Error for ConventionalCommitError {}
+
+impl ConventionalCommit
+{
+    pub fn parse(message: &str) -> Result<ConventionalCommit, ConventionalCommitError>
+    {
+        let message = message.replace("\r\n", "\n");
+        let mut lines = message.trim().lines();
+        let header = lines.next().ok_or(ConventionalCommitError::Empty)?.trim_end();
+        if header.is_empty()
+        {
+            return Err(ConventionalCommitError::Empty);
+        }
+
+        // <type>[(<scope>)][!]: <description>
+        let kind_end = header.find(|x: char| !(x.is_ascii_alphanumeric() || x == '-' || x == '_')).unwrap_or(header.len());
+        let kind = &header[..kind_end];
+        if kind.is_empty()
+        {
+            return Err(ConventionalCommitError::MissingType(header.to_string()));
+        }
+
+        let mut rest = &header[kind_end..];
+        let mut scope = None;
+        if let Some(after) = rest.strip_prefix('(')
+        {
+            let end = after.find(')').ok_or_else(|| ConventionalCommitError::InvalidScope(header.to_string()))?;
+            let value = after[..end].trim();
+            if value.is_empty() || value.contains('(')
+            {
+                return Err(ConventionalCommitError::InvalidScope(header.to_string()));
+            }
+            scope = Some(value.to_string());
+            rest = &after[end + 1..];
+        }
+
+        let mut breaking = false;
+        if let Some(after) = rest.strip_prefix('!')
+        {
+            breaking = true;
+            rest = after;
+        }
+
+        let description = rest.strip_prefix(':')
+            .filter(|x| x.starts_with(' '))
+            .ok_or_else(|| ConventionalCommitError::MissingSeparator(header.to_string()))?
+            .trim();
+        if description.is_empty()
+        {
+            return Err(ConventionalCommitError::MissingDescription(header.to_string()));
+        }
+
+        let rest = lines.collect::<Vec<&str>>().join("\n");
+        let (body, footers) = split_footers(&rest);
+
+        breaking |= footers.iter().any(|x| x.token == "BREAKING CHANGE" || x.token == "BREAKING-CHANGE");
+
+        Ok(ConventionalCommit {
+            kind: kind.to_string(),
+            scope,
+            breaking,
+            description: description.to_string(),
+            body: Some(body.to_string()).filter(|x| !x.is_empty()),
+            footers,
+        })
+    }
+}
+
+/// Splits the text after a header into the body and the footers.
+///
+/// The body and footers are separated by blank lines; the footers are the last paragraph, like git trailers.
+pub fn split_footers(text: &str) -> (&str, Vec<Footer>)
+{
+    let text = text.trim();
+    match text.rfind("\n\n").map_or(text, |x| &text[x + 2..])
+    {
+        last if footer(last.lines().next().unwrap_or("")).is_some() => (text[..text.len() - last.len()].trim(), parse_footers(last)),
+        _ => (text, Vec::new()),
+    }
+}
+
+/// Splits a footer line into its token and value.
+fn footer(line: &str) -> Option<(&str, &str)>
+{
+    if let Some(value) = line.strip_prefix("BREAKING CHANGE: ")
+    {
+        return Some(("BREAKING CHANGE", value));
+    }
+    // A breaking change does not need to be described.
+    if let Some(token) = ["BREAKING CHANGE", "BREAKING-CHANGE"].into_iter().find(|x| line.strip_prefix(x).is_some_and(|x| x.trim_end() == ":"))
+    {
+        return Some((token, ""));
+    }
+
+    let token_end = line.find(|x: char| !(x.is_ascii_alphanumeric() || x == '-'))?;
+    let token = &line[..token_end];
+    if token.is_empty()
+    {
+        return None;
+    }
+
+    let rest = &line[token_end..];
+    if let Some(value) = rest.strip_prefix(": ")
+    {
+        Some((token, value))
+    }
+    else if rest.starts_with(" #")
+    {
+        Some((token, &rest[1..]))
+    }
+    else
+    {
+        None
+    }
+}
+
+/// Lines that do not start a footer continue the value of the previous one.
+fn parse_footers(block: &str) -> Vec<Footer>
+{
+    let mut footers = Vec::<Footer>::new();
+    for line in block.lines()
+    {
+        match (footer(line), footers.last_mut())
+        {
+            (Some((token, value)), _) => footers.push(Footer { token: token.to_string(), value: value.trim().to_string() }),
+            (None, Some(last)) => {
+                last.value.push('\n');
+                last.value.push_str(line.trim());
+            }
+            (None, None) => {}
+        }
+    }
+    footers
+}
+
+/// Checks a commit message as git leaves it for a `commit-msg` hook, e.g. `.git/COMMIT_EDITMSG`;
+/// `#` comment lines and everything below the scissors line of `git commit -v` are ignored.
+pub fn lint(message: &str) -> Result<ConventionalCommit, ConventionalCommitError>
+{
+    let message = message.replace("\r\n", "\n");
+    let message = message.lines()
+        .take_while(|x| !x.starts_with("# ------------------------ >8 ------------------------"))
+        .filter(|x| !x.starts_with('#'))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    ConventionalCommit::parse(&message)
+}
+
+impl FromStr for ConventionalCommit
+{
+    type Err = ConventionalCommitError;
+
+    fn from_str(message: &str) -> Result<Self, Self::Err>
+    {
+        ConventionalCommit::parse(message)
+    }
+}
+
+/// Renders the normalized message.
+impl Display for ConventionalCommit
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(scope) = &self.scope
+        {
+            write!(f, "({})", scope)?;
+        }
+        // A breaking change footer already says so.
+        if self.breaking && !self.footers.iter().any(|x| x.token.starts_with("BREAKING"))
+        {
+            write!(f, "!")?;
+        }
+        write!(f, ": {}", self.description)?;
+
+        if let Some(body) = &self.body
+        {
+            write!(f, "\n\n{}", body)?;
+        }
+        for (index, footer) in self.footers.iter().enumerate()
+        {
+            let separator = if footer.value.starts_with('#') { " " } else if footer.value.is_empty() { ":" } else { ": " };
+            write!(f, "{}{}{}{}", if index == 0 { "\n\n" } else { "\n" }, footer.token, separator, footer.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parse()
+{
+    let commit = ConventionalCommit::parse("feat(api-v2)!: drop the v1 endpoints\n\nThe v1 endpoints were deprecated\na year ago.\n\nSecond paragraph.\n\nBREAKING CHANGE: clients need v2\n  to keep working\nRefs #133\nReviewed-by: Name").unwrap();
+    assert_eq!(commit.kind, "feat");
+    assert_eq!(commit.scope.as_deref(), Some("api-v2"));
+    assert!(commit.breaking);
+    assert_eq!(commit.description, "drop the v1 endpoints");
+    assert_eq!(commit.body.as_deref(), Some("The v1 endpoints were deprecated\na year ago.\n\nSecond paragraph."));
+    assert_eq!(commit.footers, [
+        Footer { token: "BREAKING CHANGE".to_string(), value: "clients need v2\nto keep working".to_string() },
+        Footer { token: "Refs".to_string(), value: "#133".to_string() },
+        Footer { token: "Reviewed-by".to_string(), value: "Name".to_string() },
+    ]);
+
+    let cases = [
+        ("fix: typo", "fix", None, false),
+        ("fix(core/net): retry", "fix", Some("core/net"), false),
+        ("refactor!: rename", "refactor", None, true),
+        ("chore(deps): bump\n\nBREAKING-CHANGE: needs rust 1.80", "chore", Some("deps"), true),
+        // A footer lookalike in the body is not a footer.
+        ("docs: notes\n\nNote: this is body text\n\nMore body.", "docs", None, false),
+        ("feat: drop v1\n\nBREAKING CHANGE:", "feat", None, true),
+        ("feat: drop v1\n\nRefs #1\nBREAKING-CHANGE:", "feat", None, true),
+    ];
+    for (message, kind, scope, breaking) in cases.iter()
+    {
+        let commit = message.parse::<ConventionalCommit>().unwrap_or_else(|error| panic!("{} - {}", message, error));
+        assert_eq!((commit.kind.as_str(), commit.scope.as_deref(), commit.breaking), (*kind, *scope, *breaking), "{}", message);
+    }
+    assert_eq!(ConventionalCommit::parse("docs: notes\n\nNote: this is body text\n\nMore body.").unwrap().footers, []);
+
+    // Round-trips through the normalized message.
+    for message in ["feat(ui): dark mode\n\nBody.\n\nRefs #12", "fix!: crash", "chore: release\n\nBREAKING CHANGE: gone", "chore: release\n\nBREAKING CHANGE:"]
+    {
+        assert_eq!(ConventionalCommit::parse(message).unwrap().to_string(), message);
+    }
+
+    assert_eq!(ConventionalCommit::parse(""), Err(ConventionalCommitError::Empty));
+    assert!(matches!(ConventionalCommit::parse("Update README"), Err(ConventionalCommitError::MissingSeparator(_))));
+    assert!(matches!(ConventionalCommit::parse(": nothing"), Err(ConventionalCommitError::MissingType(_))));
+    assert!(matches!(ConventionalCommit::parse("feat(): x"), Err(ConventionalCommitError::InvalidScope(_))));
+    assert!(matches!(ConventionalCommit::parse("feat(api: x"), Err(ConventionalCommitError::InvalidScope(_))));
+    assert!(matches!(ConventionalCommit::parse("feat:x"), Err(ConventionalCommitError::MissingSeparator(_))));
+    assert!(matches!(ConventionalCommit::parse("feat: "), Err(ConventionalCommitError::MissingSeparator(_) | ConventionalCommitError::MissingDescription(_))));
+}
+
+#[test]
+fn test_lint()
+{
+    let message = "fix: typo\n\nRefs #12\n# Please enter the commit message for your changes.\n# ------------------------ >8 ------------------------\ndiff --git a/README.md b/README.md\n";
+    assert_eq!(lint(message).unwrap().to_string(), "fix: typo\n\nRefs #12");
+    assert_eq!(lint("# Please enter the commit message for your changes.\n"), Err(ConventionalCommitError::Empty));
+    assert!(lint("Update README\n").is_err());
+}
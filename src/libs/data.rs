@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
+
+use super::{classification::{self, CommitRule, RuleError}, requirement::{VersionReq, VersionReqParseError}, scheme::{self, SchemeError, VersioningScheme}, tag_format::{TagFormat, TagFormatError}, version::{CommitType, SemanticVersion, VersionParseError}};
 
 #[derive(serde::Deserialize, Debug)]
 pub struct SemverDataTaggingRepository
@@ -9,6 +11,39 @@ pub struct SemverDataTaggingRepository
 pub struct SemverDataTagging
 {
     pub supported_repositories: HashMap<String, SemverDataTaggingRepository>,
+    /// Template for tag names, see [`TagFormat`]; defaults to the format of the existing tags, see [`TagFormat::detect`].
+    pub tag_format: Option<String>,
+    /// What to do with releases whose tag already exists or whose version goes backwards; defaults to `abort`.
+    pub on_conflict: Option<OnConflict>,
+    /// Only tags matching this regex are considered when looking for previous releases.
+    pub include: Option<String>,
+    /// Tags matching this regex are ignored when looking for previous releases, e.g. `^(docs|deploy)-`.
+    pub exclude: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict
+{
+    /// Stop before anything is tagged.
+    #[default]
+    Abort,
+    /// Leave the conflicting releases out and tag the others.
+    Skip,
+}
+
+impl SemverDataTagging
+{
+    /// The configured tag format, or the one the existing tags follow.
+    pub fn tag_format(&self, scheme: Rc<dyn VersioningScheme>, tag_names: &[String]) -> Result<TagFormat, TagFormatError>
+    {
+        let tag_format = match self.tag_format.as_deref()
+        {
+            Some(template) => TagFormat::new(template)?.with_scheme(scheme),
+            None => TagFormat::detect(tag_names, scheme),
+        };
+        tag_format.with_filters(self.include.as_deref(), self.exclude.as_deref())
+    }
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -16,23 +51,196 @@ pub struct SemverDataBranch
 {
     pub name: String,
     pub prerelease: Option<bool>,
-    pub increment: Option<Vec<String>>
+    /// Pre-release channel of the branch, e.g. `rc`, `beta` or `alpha`; defaults to `rc`.
+    pub prerelease_identifier: Option<String>,
+    /// Versions the branch may release, e.g. `~1.4` for a maintenance branch; see [`VersionReq`].
+    pub range: Option<String>,
+    pub increment: Option<Vec<String>>,
+    /// Only follow the first parent of merges, so the commits of merged branches are not counted one by one; defaults to `false`.
+    pub first_parent: Option<bool>,
+    /// Classify merge commits by the pull request title in their body instead of `Merge pull request #...`; defaults to `false`.
+    pub merge_title: Option<bool>,
+}
+
+impl SemverDataBranch
+{
+    pub fn range(&self) -> Result<Option<VersionReq>, VersionReqParseError>
+    {
+        self.range.as_deref().map(VersionReq::parse).transpose()
+    }
 }
 
 #[derive(serde::Deserialize, Debug)]
 pub struct SemverDataCommits
 {
+    /// Increment of commits that no rule matches, and of `map` keys that are not an increment; `MAJOR`, `MINOR`, `PATCH` or `NONE`.
     pub default: String,
     #[serde(alias = "caseSensitive")]
     pub case_sensitive: bool,
     pub release: Vec<String>,
     pub prerelease: Vec<String>,
-    pub map: HashMap<String, Vec<String>>
+    /// Legacy classification by type, e.g. `"MINOR": ["feat"]` or `"NONE": ["docs"]`; tried after `rules`, from MAJOR to NONE.
+    #[serde(default)]
+    pub map: HashMap<String, Vec<String>>,
+    /// Classification rules, see [`CommitRule`]; commits that no rule matches take the `default`.
+    pub rules: Option<Vec<SemverDataRule>>,
+    /// Classify each line of a squash merge body that follows the format, e.g. `* feat: a`, as a change of its own, whatever the title; defaults to `false`.
+    pub squash: Option<bool>,
+    /// List the commits of the `NONE` level, which never bump the version, in the release notes; defaults to `false`.
+    pub list_none: Option<bool>,
+    /// Markers that leave commits out, or skip the whole run.
+    pub skip: Option<SemverDataSkip>,
+}
+
+/// Markers are matched anywhere in the commit message, ignoring case; an empty list turns them off.
+#[derive(serde::Deserialize, Debug)]
+pub struct SemverDataSkip
+{
+    /// Leave the commit out of the analysis; defaults to `[skip release]`, `[no version]` and `Release: skip`.
+    pub commit: Option<Vec<String>>,
+    /// Make the whole run a no-op when found on the analysed commit, HEAD by default; defaults to `[skip ci]` and `[ci skip]`.
+    pub run: Option<Vec<String>>,
+}
+
+/// A classification rule; a commit matches when it matches every condition that is set.
+#[derive(serde::Deserialize, Debug)]
+pub struct SemverDataRule
+{
+    /// `MAJOR`, `MINOR`, `PATCH` or `NONE`.
+    pub increment: String,
+    /// The conventional type, e.g. `feat`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Regex on the first line of the message.
+    pub subject: Option<String>,
+    /// Regex on the rest of the message.
+    pub body: Option<String>,
+    /// Token of a footer the commit must have, e.g. `BREAKING CHANGE`.
+    pub footer: Option<String>,
+    /// Rules with a higher priority are tried first, in their order otherwise; defaults to 0.
+    pub priority: Option<i32>,
+}
+
+impl SemverDataCommits
+{
+    /// The configured rules followed by the `map`, ordered by priority.
+    pub fn rules(&self) -> Result<Vec<CommitRule>, RuleError>
+    {
+        let mut rules = self.rules.iter().flatten()
+            .map(|x| CommitRule::new(&x.increment, x.kind.as_deref(), x.subject.as_deref(), x.body.as_deref(), x.footer.as_deref(), x.priority.unwrap_or(0), self.case_sensitive))
+            .collect::<Result<Vec<CommitRule>, RuleError>>()?;
+
+        // The keys of the map have no order, so they go from the highest bump; other keys take the default, by name.
+        let rank = |key: &str| classification::parse_increment(key).map_or(4, CommitType::rank);
+        let mut keys = self.map.keys().collect::<Vec<&String>>();
+        keys.sort_by_key(|x| (rank(x), x.to_string()));
+        for key in keys
+        {
+            let increment = match (rank(key), rank(&self.default))
+            {
+                (0..=3, _) => key.as_str(),
+                (_, 0..=3) => self.default.as_str(),
+                _ => "PATCH",
+            };
+            for value in self.map[key].iter()
+            {
+                rules.push(CommitRule::new(increment, Some(value), None, None, None, 0, self.case_sensitive)?);
+            }
+        }
+
+        classification::prioritize(rules)
+    }
+
+    /// Commit type of commits that no rule matches.
+    pub fn default_type(&self) -> Result<CommitType, RuleError>
+    {
+        classification::parse_increment(&self.default)
+    }
+
+    pub fn skip_commit_markers(&self) -> Vec<String>
+    {
+        self.skip.as_ref().and_then(|x| x.commit.clone())
+            .unwrap_or_else(|| ["[skip release]", "[no version]", "Release: skip"].map(String::from).to_vec())
+    }
+
+    pub fn skip_run_markers(&self) -> Vec<String>
+    {
+        self.skip.as_ref().and_then(|x| x.run.clone())
+            .unwrap_or_else(|| ["[skip ci]", "[ci skip]"].map(String::from).to_vec())
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SemverDataVersioning
+{
+    /// `semver` or `calver`; defaults to `semver`.
+    pub scheme: Option<String>,
+    /// CalVer only, e.g. `YYYY.0M.MICRO`; see [`scheme::CalVer`].
+    pub format: Option<String>,
+    /// SemVer only, treat breaking changes as minor and features as patch changes while the major version is 0; defaults to `true`.
+    pub initial_development: Option<bool>,
 }
 
 #[derive(serde::Deserialize, Debug)]
 pub struct SemverData {
     pub tagging: SemverDataTagging,
     pub branches: Vec<SemverDataBranch>,
-    pub commits: SemverDataCommits
-}
\ No newline at end of file
+    pub commits: SemverDataCommits,
+    pub versioning: Option<SemverDataVersioning>,
+    /// Version of the first release when there are no version tags yet, e.g. `1.0.0`; by default the first release is bumped from 0.0.0.
+    pub initial_version: Option<String>,
+}
+
+impl SemverData
+{
+    /// The configured versioning scheme, semver when there is none.
+    pub fn scheme(&self) -> Result<Rc<dyn VersioningScheme>, SchemeError>
+    {
+        match &self.versioning
+        {
+            Some(versioning) => scheme::from_config(
+                versioning.scheme.as_deref().unwrap_or("semver"),
+                versioning.format.as_deref(),
+                versioning.initial_development.unwrap_or(true)
+            ),
+            None => Ok(Rc::new(scheme::SemVer::default())),
+        }
+    }
+
+    pub fn initial_version(&self, scheme: &dyn VersioningScheme) -> Result<Option<SemanticVersion>, VersionParseError>
+    {
+        self.initial_version.as_deref().map(|x| scheme.parse(x)).transpose()
+    }
+}
+#[test]
+fn test_rules()
+{
+    use super::classification::CommitFacts;
+
+    let commits: SemverDataCommits = serde_json::from_value(serde_json::json!({
+        "default": "MINOR",
+        "caseSensitive": false,
+        "release": [],
+        "prerelease": [],
+        "rules": [
+            { "increment": "PATCH", "type": "feat", "subject": "^feat\\(docs\\)" },
+            { "increment": "MAJOR", "footer": "BREAKING CHANGE", "priority": 10 }
+        ],
+        "map": {
+            "PATCH": ["fix", "chore"],
+            "OTHER": ["chore"],
+            "MINOR": ["feat"]
+        }
+    })).unwrap();
+    let rules = commits.rules().unwrap();
+
+    let footers = [super::conventional::Footer { token: "BREAKING CHANGE".to_string(), value: "gone".to_string() }];
+    let classify = |kind, subject, footers| classification::classify(&rules, &CommitFacts { kind, subject, body: "", footers });
+    assert_eq!(classify("feat", "feat(docs): guide", &[]), Some(CommitType::Patch));
+    assert_eq!(classify("feat", "feat(ui): dark mode", &[]), Some(CommitType::Minor));
+    assert_eq!(classify("fix", "fix: typo", &footers), Some(CommitType::Major));
+    // PATCH comes before the unknown key, whatever the order of the map.
+    assert_eq!(classify("chore", "chore: deps", &[]), Some(CommitType::Patch));
+    assert_eq!(classify("style", "style: format", &[]), None);
+    assert_eq!(commits.default_type().unwrap(), CommitType::Minor);
+}
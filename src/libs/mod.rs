@@ -1,3 +1,8 @@
 pub mod version;
+pub mod requirement;
 pub mod data;
-pub mod release;
\ No newline at end of file
+pub mod release;
+pub mod tag_format;
+pub mod scheme;
+pub mod conventional;
+pub mod classification;
\ No newline at end of file
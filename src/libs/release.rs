@@ -1,15 +1,17 @@
 use git2::Oid;
+use serde::ser::SerializeStruct;
 
 use super::version::SemanticVersion;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ReleaseContributor
 {
     pub name: String,
     pub email: String,
 }
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ReleaseType
 {
     None,
@@ -17,14 +19,118 @@ pub enum ReleaseType
     PreRelease,
 }
 
+/// A planned release.
+///
+/// Serializes to the following schema, e.g. for `--output-json`:
+///
+/// ```json
+/// {
+///   "version": "1.3.0-rc.1",
+///   "pinned": false,
+///   "rejected": ["2.0.0 is not higher than 3.0.0 (fix!: crash)"],
+///   "type": "prerelease",
+///   "commit": "8e80156d3f1b4e0a9c1f2b7d6e5a4c3b2a190817",
+///   "changes": {
+///     "major": [],
+///     "minor": ["feat: add calendar versioning"],
+///     "patch": ["fix: handle detached HEAD"],
+///     "revert": [],
+///     "other": ["docs: explain tag formats"],
+///     "skipped": ["fix: flaky test [skip release] (skipped by [skip release])"]
+///   },
+///   "contributors": [{ "name": "Jane Doe", "email": "jane@example.com" }]
+/// }
+/// ```
+///
+/// `type` is one of `release`, `prerelease` or `none`, and `commit` is the full hex id of the released commit.
+/// A `none` entry is never tagged; it only reports the commits skipped in a run that releases nothing.
+/// `pinned` is set when the version comes from a `Release-As` footer, `rejected` lists the `Release-As` footers that were refused
+/// and why, `other` lists commits that did not bump the version and `skipped` the commits left out by a marker.
+/// Changes are commit messages without their footers, normalized when they follow Conventional Commits.
 #[derive(Debug, Clone)]
 pub struct Release
 {
     pub commit:         Oid,  
     pub tag:            ReleaseType,
     pub version:        SemanticVersion,
+    /// The version was pinned by a `Release-As` footer rather than computed.
+    pub pinned:         bool,
+    /// `Release-As` footers that were refused, with the reason and the subject of their commit.
+    pub rejected:       Vec<String>,
     pub majors:         Vec<String>,
     pub minors:         Vec<String>,
     pub patches:        Vec<String>,
+    /// Reverts of commits released before; unreleased commits that are reverted are left out along with their reverts.
+    pub reverts:        Vec<String>,
+    /// Commits of the `NONE` level, when they are listed at all.
+    pub others:         Vec<String>,
+    /// Commits left out by a skip marker, with the marker.
+    pub skipped:        Vec<String>,
     pub contributors:   Vec<ReleaseContributor>,
 }
+
+#[derive(serde::Serialize)]
+struct ReleaseChanges<'a>
+{
+    major: &'a [String],
+    minor: &'a [String],
+    patch: &'a [String],
+    revert: &'a [String],
+    other: &'a [String],
+    skipped: &'a [String],
+}
+
+impl serde::Serialize for Release
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("Release", 7)?;
+        state.serialize_field("version", &self.version)?;
+        state.serialize_field("pinned", &self.pinned)?;
+        state.serialize_field("rejected", &self.rejected)?;
+        state.serialize_field("type", &self.tag)?;
+        state.serialize_field("commit", &self.commit.to_string())?;
+        state.serialize_field("changes", &ReleaseChanges { major: &self.majors, minor: &self.minors, patch: &self.patches, revert: &self.reverts, other: &self.others, skipped: &self.skipped })?;
+        state.serialize_field("contributors", &self.contributors)?;
+        state.end()
+    }
+}
+
+#[test]
+fn test_serialize()
+{
+    let release = Release {
+        commit: Oid::from_str("8e80156d3f1b4e0a9c1f2b7d6e5a4c3b2a190817").unwrap(),
+        tag: ReleaseType::PreRelease,
+        version: SemanticVersion::parse("1.3.0-rc.1+build.7").unwrap(),
+        pinned: false,
+        rejected: vec!["2.0.0 is not higher than 3.0.0 (fix!: crash)".to_string()],
+        majors: vec![],
+        minors: vec!["feat: add calendar versioning".to_string()],
+        patches: vec!["fix: handle detached HEAD".to_string()],
+        reverts: vec!["revert: feat: add json output".to_string()],
+        others: vec!["docs: explain tag formats".to_string()],
+        skipped: vec!["fix: flaky test [skip release] (skipped by [skip release])".to_string()],
+        contributors: vec![ReleaseContributor { name: "Jane Doe".to_string(), email: "jane@example.com".to_string() }],
+    };
+
+    assert_eq!(serde_json::to_value(&release).unwrap(), serde_json::json!({
+        "version": "1.3.0-rc.1+build.7",
+        "pinned": false,
+        "rejected": ["2.0.0 is not higher than 3.0.0 (fix!: crash)"],
+        "type": "prerelease",
+        "commit": "8e80156d3f1b4e0a9c1f2b7d6e5a4c3b2a190817",
+        "changes": {
+            "major": [],
+            "minor": ["feat: add calendar versioning"],
+            "patch": ["fix: handle detached HEAD"],
+            "revert": ["revert: feat: add json output"],
+            "other": ["docs: explain tag formats"],
+            "skipped": ["fix: flaky test [skip release] (skipped by [skip release])"]
+        },
+        "contributors": [{ "name": "Jane Doe", "email": "jane@example.com" }]
+    }));
+
+    let version: SemanticVersion = serde_json::from_value(serde_json::json!("1.3.0-rc.1+build.7")).unwrap();
+    assert_eq!(version.to_string(), release.version.to_string());
+}
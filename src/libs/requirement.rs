@@ -0,0 +1,429 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use super::version::{compare_pre_release, parse_pre_release, Identifier, SemanticVersion, VersionComponent, VersionParseError};
+
+/// A set of comparators a version has to satisfy, in the same notation Cargo uses.
+///
+/// ```text
+/// ^1.2            >=1.2.0, <2.0.0
+/// ~1.2.3          >=1.2.3, <1.3.0
+/// >=1.0, <2.0     both have to match
+/// 1.x, 1.2.*      wildcards
+/// *               any normal version
+/// ```
+///
+/// Pre-releases only match when one of the comparators names a pre-release of the same `major.minor.patch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq
+{
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op
+{
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator
+{
+    pub op: Op,
+    pub major: u32,
+    pub minor: Option<u32>,
+    pub patch: Option<u32>,
+    pub pre_release: Vec<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionReqParseError
+{
+    /// Nothing to parse, e.g. an empty string or `>=1.0,`.
+    Empty,
+    /// A comparator with an invalid version, e.g. `>=1.y`.
+    InvalidVersion(String, VersionParseError),
+    /// A wildcard followed by a number, e.g. `1.*.3`.
+    UnexpectedWildcard(String),
+}
+
+impl Display for VersionReqParseError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            VersionReqParseError::Empty => write!(f, "empty version requirement"),
+            VersionReqParseError::InvalidVersion(comparator, error) => write!(f, "invalid version requirement `{}`: {}", comparator, error),
+            VersionReqParseError::UnexpectedWildcard(comparator) => write!(f, "invalid version requirement `{}`: only trailing wildcards are allowed", comparator),
+        }
+    }
+}
+
+impl std::error::Error for VersionReqParseError {}
+
+impl VersionReq
+{
+    pub fn parse(requirement: &str) -> Result<VersionReq, VersionReqParseError>
+    {
+        let requirement = requirement.trim();
+        if requirement.is_empty()
+        {
+            return Err(VersionReqParseError::Empty);
+        }
+        if matches!(requirement, "*" | "x" | "X")
+        {
+            return Ok(VersionReq { comparators: Vec::new() });
+        }
+
+        let comparators = requirement.split(',')
+            .map(Comparator::parse)
+            .collect::<Result<Vec<Comparator>, VersionReqParseError>>()?;
+
+        Ok(VersionReq { comparators })
+    }
+
+    pub fn matches(&self, version: &SemanticVersion) -> bool
+    {
+        if !self.comparators.iter().all(|x| x.matches(version))
+        {
+            return false;
+        }
+
+        // Opt-in for pre-releases, so `>=1.0` does not pick up `2.0.0-alpha.1`.
+        !version.is_pre_release() || self.comparators.iter().any(|x| x.allows_pre_release_of(version))
+    }
+}
+
+impl FromStr for VersionReq
+{
+    type Err = VersionReqParseError;
+
+    fn from_str(requirement: &str) -> Result<Self, Self::Err>
+    {
+        VersionReq::parse(requirement)
+    }
+}
+
+impl Display for VersionReq
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.comparators.is_empty()
+        {
+            return write!(f, "*");
+        }
+
+        let comparators = self.comparators.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        write!(f, "{}", comparators.join(", "))
+    }
+}
+
+impl Comparator
+{
+    fn parse(comparator: &str) -> Result<Comparator, VersionReqParseError>
+    {
+        let text = comparator.trim();
+        if text.is_empty()
+        {
+            return Err(VersionReqParseError::Empty);
+        }
+
+        let (op, version) = [(">=", Op::GreaterEq), ("<=", Op::LessEq), (">", Op::Greater), ("<", Op::Less), ("=", Op::Exact), ("~", Op::Tilde), ("^", Op::Caret)]
+            .iter()
+            .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|version| (Some(*op), version.trim_start())))
+            .unwrap_or((None, text));
+
+        let invalid = |error: VersionParseError| VersionReqParseError::InvalidVersion(text.to_string(), error);
+
+        // Build metadata never takes part in matching.
+        let version = version.split_once('+').map(|(version, _)| version).unwrap_or(version);
+        let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        let (core, pre_release) = match version.split_once('-')
+        {
+            Some((core, pre_release)) => (core, parse_pre_release(pre_release).map_err(invalid)?),
+            None => (version, Vec::new()),
+        };
+
+        let mut parts = core.split('.');
+        let major = parse_part(parts.next(), VersionComponent::Major).map_err(invalid)?
+            .ok_or_else(|| VersionReqParseError::UnexpectedWildcard(text.to_string()))?;
+        let minor = parse_part(parts.next(), VersionComponent::Minor).map_err(invalid)?;
+        let patch = parse_part(parts.next(), VersionComponent::Patch).map_err(invalid)?;
+        if let Some(part) = parts.next()
+        {
+            return Err(invalid(VersionParseError::Unexpected(VersionComponent::Patch, part.to_string())));
+        }
+
+        let is_wildcard = core.split('.').skip(1).any(|x| matches!(x, "*" | "x" | "X"));
+        if (minor.is_none() && patch.is_some()) || (is_wildcard && !pre_release.is_empty())
+        {
+            return Err(VersionReqParseError::UnexpectedWildcard(text.to_string()));
+        }
+
+        // `1.2` means `^1.2`, just like in Cargo; `1.x` means `1.*`.
+        let op = match op
+        {
+            Some(op) => op,
+            None if is_wildcard => Op::Wildcard,
+            None => Op::Caret,
+        };
+
+        Ok(Comparator { op, major, minor, patch, pre_release })
+    }
+
+    pub fn matches(&self, version: &SemanticVersion) -> bool
+    {
+        match self.op
+        {
+            Op::Exact | Op::Wildcard => self.matches_exact(version),
+            Op::Greater => self.matches_greater(version),
+            Op::GreaterEq => self.matches_exact(version) || self.matches_greater(version),
+            Op::Less => self.matches_less(version),
+            Op::LessEq => self.matches_exact(version) || self.matches_less(version),
+            Op::Tilde => self.matches_tilde(version),
+            Op::Caret => self.matches_caret(version),
+        }
+    }
+
+    fn allows_pre_release_of(&self, version: &SemanticVersion) -> bool
+    {
+        !self.pre_release.is_empty()
+            && self.major == version.get_major()
+            && self.minor == Some(version.get_minor())
+            && self.patch == Some(version.get_patch())
+    }
+
+    fn compare_pre_release(&self, version: &SemanticVersion) -> Ordering
+    {
+        compare_pre_release(version.get_pre_release(), &self.pre_release)
+    }
+
+    fn matches_exact(&self, version: &SemanticVersion) -> bool
+    {
+        version.get_major() == self.major
+            && self.minor.is_none_or(|minor| version.get_minor() == minor)
+            && self.patch.is_none_or(|patch| version.get_patch() == patch)
+            && (self.patch.is_none() || self.compare_pre_release(version) == Ordering::Equal)
+    }
+
+    fn matches_greater(&self, version: &SemanticVersion) -> bool
+    {
+        if version.get_major() != self.major
+        {
+            return version.get_major() > self.major;
+        }
+        match self.minor
+        {
+            None => return false,
+            Some(minor) if version.get_minor() != minor => return version.get_minor() > minor,
+            _ => {}
+        }
+        match self.patch
+        {
+            None => return false,
+            Some(patch) if version.get_patch() != patch => return version.get_patch() > patch,
+            _ => {}
+        }
+        self.compare_pre_release(version) == Ordering::Greater
+    }
+
+    fn matches_less(&self, version: &SemanticVersion) -> bool
+    {
+        if version.get_major() != self.major
+        {
+            return version.get_major() < self.major;
+        }
+        match self.minor
+        {
+            None => return false,
+            Some(minor) if version.get_minor() != minor => return version.get_minor() < minor,
+            _ => {}
+        }
+        match self.patch
+        {
+            None => return false,
+            Some(patch) if version.get_patch() != patch => return version.get_patch() < patch,
+            _ => {}
+        }
+        self.compare_pre_release(version) == Ordering::Less
+    }
+
+    fn matches_tilde(&self, version: &SemanticVersion) -> bool
+    {
+        if version.get_major() != self.major
+        {
+            return false;
+        }
+        if self.minor.is_some_and(|minor| version.get_minor() != minor)
+        {
+            return false;
+        }
+        match self.patch
+        {
+            None => true,
+            Some(patch) if version.get_patch() != patch => version.get_patch() > patch,
+            _ => self.compare_pre_release(version) != Ordering::Less,
+        }
+    }
+
+    fn matches_caret(&self, version: &SemanticVersion) -> bool
+    {
+        if version.get_major() != self.major
+        {
+            return false;
+        }
+        let minor = match self.minor
+        {
+            None => return true,
+            Some(minor) => minor,
+        };
+        let patch = match self.patch
+        {
+            // ^1.2 is >=1.2.0, <2.0.0 but ^0.2 is >=0.2.0, <0.3.0
+            None if self.major > 0 => return version.get_minor() >= minor,
+            None => return version.get_minor() == minor,
+            Some(patch) => patch,
+        };
+
+        if self.major > 0
+        {
+            if version.get_minor() != minor
+            {
+                return version.get_minor() > minor;
+            }
+        }
+        else if version.get_minor() != minor
+        {
+            return false;
+        }
+        else if minor == 0 && version.get_patch() != patch
+        {
+            // ^0.0.3 is =0.0.3
+            return false;
+        }
+
+        if version.get_patch() != patch
+        {
+            return version.get_patch() > patch;
+        }
+        self.compare_pre_release(version) != Ordering::Less
+    }
+}
+
+impl Display for Comparator
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self.op
+        {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "",
+        };
+        let wildcard = if self.op == Op::Wildcard { "*" } else { "" };
+
+        let mut version = self.major.to_string();
+        match (self.minor, self.patch)
+        {
+            (Some(minor), Some(patch)) => version = format!("{}.{}.{}", version, minor, patch),
+            (Some(minor), None) if wildcard.is_empty() => version = format!("{}.{}", version, minor),
+            (Some(minor), None) => version = format!("{}.{}.{}", version, minor, wildcard),
+            (None, _) if !wildcard.is_empty() => version = format!("{}.{}", version, wildcard),
+            (None, _) => {}
+        }
+        if !self.pre_release.is_empty()
+        {
+            let pre_release = self.pre_release.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+            version = format!("{}-{}", version, pre_release.join("."));
+        }
+
+        write!(f, "{}{}", op, version)
+    }
+}
+
+/// A single numeric part of a comparator, where `*`, `x` and `X` are wildcards.
+fn parse_part(part: Option<&str>, component: VersionComponent) -> Result<Option<u32>, VersionParseError>
+{
+    match part
+    {
+        None | Some("*") | Some("x") | Some("X") => Ok(None),
+        Some("") => Err(VersionParseError::Empty(component)),
+        Some(part) if !part.chars().all(|c| c.is_ascii_digit()) => Err(VersionParseError::InvalidCharacter(component, part.to_string())),
+        Some(part) => part.parse::<u32>().map(Some).map_err(|_| VersionParseError::Overflow(component, part.to_string())),
+    }
+}
+
+#[test]
+fn test_matches()
+{
+    let cases = [
+        ("^1.2", "1.2.0", true),
+        ("^1.2", "1.9.9", true),
+        ("^1.2", "2.0.0", false),
+        ("^1.2", "1.1.9", false),
+        ("^0.2.3", "0.2.9", true),
+        ("^0.2.3", "0.3.0", false),
+        ("^0.0.3", "0.0.4", false),
+        ("~1.2.3", "1.2.9", true),
+        ("~1.2.3", "1.3.0", false),
+        ("~1.2.3", "1.2.2", false),
+        (">=1.0, <2.0", "1.5.0", true),
+        (">=1.0, <2.0", "2.0.0", false),
+        (">=1.0, <2.0", "0.9.0", false),
+        (">1.2", "1.2.9", false),
+        (">1.2", "1.3.0", true),
+        ("<=1.2", "1.2.9", true),
+        ("1.x", "1.9.0", true),
+        ("1.x", "2.0.0", false),
+        ("1.2.*", "1.2.7", true),
+        ("1.2.*", "1.3.0", false),
+        ("*", "3.1.4", true),
+        ("=1.2.3", "1.2.3", true),
+        ("=1.2.3", "1.2.4", false),
+        ("v2.0", "2.1.0", true),
+        // Pre-releases only match when asked for explicitly.
+        (">=1.0", "2.0.0-alpha.1", false),
+        ("*", "2.0.0-alpha.1", false),
+        (">=2.0.0-alpha.1", "2.0.0-alpha.2", true),
+        (">=2.0.0-alpha.1", "2.0.0", true),
+        (">=2.0.0-alpha.1", "2.0.1-alpha.1", false),
+        ("^1.2.3-rc.1", "1.2.3-rc.2", true),
+        ("^1.2.3-rc.2", "1.2.3-rc.1", false),
+    ];
+
+    for (requirement, version, expected) in cases.iter()
+    {
+        let parsed = VersionReq::parse(requirement).unwrap_or_else(|error| panic!("{}", error));
+        let version = SemanticVersion::parse(version).unwrap();
+        assert_eq!(parsed.matches(&version), *expected, "{} matches {}", requirement, version);
+    }
+
+    assert_eq!(VersionReq::parse(">=1.0,<2").unwrap().to_string(), ">=1.0, <2");
+    assert_eq!(VersionReq::parse("1.x").unwrap().to_string(), "1.*");
+    assert!(VersionReq::parse("").is_err());
+    assert!(VersionReq::parse(">=1.0,").is_err());
+    assert!(VersionReq::parse("1.*.3").is_err());
+    assert!(VersionReq::parse(">=1.y").is_err());
+
+    let invalid = |requirement: &str| match VersionReq::parse(requirement)
+    {
+        Err(VersionReqParseError::InvalidVersion(_, error)) => error,
+        other => panic!("{} - {:?}", requirement, other),
+    };
+    assert_eq!(invalid(">=y.1"), VersionParseError::InvalidCharacter(VersionComponent::Major, "y".to_string()));
+    assert_eq!(invalid(">=1.y"), VersionParseError::InvalidCharacter(VersionComponent::Minor, "y".to_string()));
+    assert_eq!(invalid("~1.2.3z"), VersionParseError::InvalidCharacter(VersionComponent::Patch, "3z".to_string()));
+    assert_eq!(invalid(">=1..0"), VersionParseError::Empty(VersionComponent::Minor));
+    assert_eq!(invalid(">=1.0.0-"), VersionParseError::Empty(VersionComponent::PreRelease));
+    assert_eq!(invalid("1.0.0-a..b"), VersionParseError::Empty(VersionComponent::PreRelease));
+    assert_eq!(invalid("^1.0.0-rc.01"), VersionParseError::LeadingZero(VersionComponent::PreRelease, "01".to_string()));
+}
@@ -0,0 +1,307 @@
+use std::{fmt::Display, rc::Rc};
+
+use super::version::{CommitType, SemanticVersion, VersionParseError};
+
+/// How versions are incremented, rendered and read back.
+///
+/// Every scheme stores its versions as a [`SemanticVersion`], so commit analysis, tagging and releases
+/// work the same way regardless of the scheme.
+pub trait VersioningScheme: std::fmt::Debug
+{
+    fn name(&self) -> &'static str;
+
+    /// Bumps the version for a commit of the type, made at `timestamp` (seconds since the epoch, in the committer's time zone).
+    fn increment(&self, version: &mut SemanticVersion, commit_type: &CommitType, timestamp: i64);
+
+    fn format(&self, version: &SemanticVersion) -> String;
+
+    fn parse(&self, version: &str) -> Result<SemanticVersion, VersionParseError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum SchemeError
+{
+    UnknownScheme(String),
+    InvalidFormat(String),
+}
+
+impl Display for SchemeError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            SchemeError::UnknownScheme(scheme) => write!(f, "unknown versioning scheme `{}`, expected `semver` or `calver`", scheme),
+            SchemeError::InvalidFormat(reason) => write!(f, "invalid versioning format: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SchemeError {}
+
+/// Creates the scheme by its configuration name.
+pub fn from_config(scheme: &str, format: Option<&str>, initial_development: bool) -> Result<Rc<dyn VersioningScheme>, SchemeError>
+{
+    match scheme.to_lowercase().as_str()
+    {
+        "semver" => Ok(Rc::new(SemVer { initial_development })),
+        "calver" => Ok(Rc::new(CalVer::new(format.unwrap_or(CalVer::DEFAULT))?)),
+        _ => Err(SchemeError::UnknownScheme(scheme.to_string())),
+    }
+}
+
+/// <https://semver.org/>: MAJOR.MINOR.PATCH, bumped by the commit type.
+#[derive(Debug, Clone)]
+pub struct SemVer
+{
+    /// While the major version is 0, breaking changes bump the minor and features the patch version, like Cargo does.
+    pub initial_development: bool,
+}
+
+impl Default for SemVer
+{
+    fn default() -> Self
+    {
+        SemVer { initial_development: true }
+    }
+}
+
+impl VersioningScheme for SemVer
+{
+    fn name(&self) -> &'static str { "semver" }
+
+    fn increment(&self, version: &mut SemanticVersion, commit_type: &CommitType, _timestamp: i64)
+    {
+        if self.initial_development && version.get_major() == 0
+        {
+            match commit_type
+            {
+                CommitType::Major => version.increment(&CommitType::Minor),
+                CommitType::Minor | CommitType::Patch => version.increment(&CommitType::Patch),
+                CommitType::None => {},
+            }
+        }
+        else
+        {
+            version.increment(commit_type);
+        }
+    }
+
+    fn format(&self, version: &SemanticVersion) -> String
+    {
+        version.to_string()
+    }
+
+    fn parse(&self, version: &str) -> Result<SemanticVersion, VersionParseError>
+    {
+        SemanticVersion::parse(version)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalVerToken
+{
+    /// Full year, `2026`.
+    FullYear,
+    /// Short year, `26`, or `106` for 2106 and `99` for 1999.
+    ShortYear,
+    /// Zero padded short year, `06`.
+    PaddedYear,
+    /// Month, `3`.
+    Month,
+    /// Zero padded month, `03`.
+    PaddedMonth,
+    /// Day of the month, `7`.
+    Day,
+    /// Zero padded day of the month, `07`.
+    PaddedDay,
+    /// Counter of releases within the same date.
+    Micro,
+}
+
+/// <https://calver.org/>: three dot-separated segments such as `YYYY.MM.MICRO` or `YY.0M.MICRO`.
+///
+/// Supported segments are `YYYY`, `YY`, `0Y`, `MM`, `0M`, `DD`, `0D` and `MICRO`, which may only come last.
+/// Any commit moves the version to the date of the commit; `MICRO` counts the releases made on the same date.
+#[derive(Debug, Clone)]
+pub struct CalVer
+{
+    tokens: [CalVerToken; 3],
+}
+
+impl CalVer
+{
+    pub const DEFAULT: &'static str = "YYYY.MM.MICRO";
+
+    pub fn new(format: &str) -> Result<CalVer, SchemeError>
+    {
+        let tokens = format.split('.')
+            .map(|x| match x
+            {
+                "YYYY" => Ok(CalVerToken::FullYear),
+                "YY" => Ok(CalVerToken::ShortYear),
+                "0Y" => Ok(CalVerToken::PaddedYear),
+                "MM" => Ok(CalVerToken::Month),
+                "0M" => Ok(CalVerToken::PaddedMonth),
+                "DD" => Ok(CalVerToken::Day),
+                "0D" => Ok(CalVerToken::PaddedDay),
+                "MICRO" => Ok(CalVerToken::Micro),
+                _ => Err(SchemeError::InvalidFormat(format!("unknown segment `{}` in `{}`", x, format))),
+            })
+            .collect::<Result<Vec<CalVerToken>, SchemeError>>()?;
+
+        let tokens: [CalVerToken; 3] = tokens.try_into()
+            .map_err(|_| SchemeError::InvalidFormat(format!("`{}` needs exactly three segments", format)))?;
+        if tokens[..2].contains(&CalVerToken::Micro)
+        {
+            return Err(SchemeError::InvalidFormat(format!("MICRO must be the last segment of `{}`", format)));
+        }
+
+        Ok(CalVer { tokens })
+    }
+}
+
+impl VersioningScheme for CalVer
+{
+    fn name(&self) -> &'static str { "calver" }
+
+    fn increment(&self, version: &mut SemanticVersion, _commit_type: &CommitType, timestamp: i64)
+    {
+        let (year, month, day) = civil_date(timestamp);
+
+        let mut parts = [version.get_major(), version.get_minor(), version.get_patch()];
+        let mut same_date = true;
+        for (part, token) in parts.iter_mut().zip(self.tokens.iter())
+        {
+            let value = match token
+            {
+                CalVerToken::FullYear => year,
+                // Commits from before 2000, e.g. of imported histories, keep the last two digits.
+                CalVerToken::ShortYear | CalVerToken::PaddedYear => if year >= 2000 { year - 2000 } else { year % 100 },
+                CalVerToken::Month | CalVerToken::PaddedMonth => month,
+                CalVerToken::Day | CalVerToken::PaddedDay => day,
+                CalVerToken::Micro => continue,
+            };
+            same_date &= *part == value;
+            *part = value;
+        }
+
+        if self.tokens[2] == CalVerToken::Micro
+        {
+            // Like semver, a pre-release of today's version becomes that version rather than the next one.
+            parts[2] = match (same_date, version.is_pre_release())
+            {
+                (true, true) => parts[2],
+                (true, false) => parts[2] + 1,
+                (false, _) => 0,
+            };
+        }
+
+        *version = SemanticVersion::from(parts[0], parts[1], parts[2]);
+    }
+
+    fn format(&self, version: &SemanticVersion) -> String
+    {
+        let parts = [version.get_major(), version.get_minor(), version.get_patch()];
+        let mut formatted = parts.iter().zip(self.tokens.iter())
+            .map(|(part, token)| match token
+            {
+                CalVerToken::PaddedYear | CalVerToken::PaddedMonth | CalVerToken::PaddedDay => format!("{:02}", part),
+                _ => part.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(".");
+
+        // Pre-release and build metadata are rendered exactly like semver does.
+        let version = version.to_string();
+        if let Some(index) = version.find(['-', '+'])
+        {
+            formatted.push_str(&version[index..]);
+        }
+        formatted
+    }
+
+    fn parse(&self, version: &str) -> Result<SemanticVersion, VersionParseError>
+    {
+        // Lenient parsing accepts the zero padded segments.
+        SemanticVersion::parse(version)
+    }
+}
+
+/// Year, month and day of the timestamp, see <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_date(timestamp: i64) -> (u32, u32, u32)
+{
+    let days = timestamp.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year as u32, month as u32, day as u32)
+}
+
+#[test]
+fn test_calver()
+{
+    // 2026-10-03 12:00:00 UTC and the day after.
+    let october_third = 1791028800;
+    let october_fourth = october_third + 86400;
+    assert_eq!(civil_date(october_third), (2026, 10, 3));
+    assert_eq!(civil_date(951782400), (2000, 2, 29));
+
+    let scheme = CalVer::new("YYYY.MM.MICRO").unwrap();
+    let mut version = SemanticVersion::new();
+    scheme.increment(&mut version, &CommitType::Patch, october_third);
+    assert_eq!(scheme.format(&version), "2026.10.0");
+    scheme.increment(&mut version, &CommitType::Major, october_third);
+    assert_eq!(scheme.format(&version), "2026.10.1");
+
+    let scheme = CalVer::new("YY.0M.0D").unwrap();
+    let mut version = SemanticVersion::new();
+    scheme.increment(&mut version, &CommitType::Minor, october_fourth);
+    assert_eq!(scheme.format(&version), "26.10.04");
+    assert_eq!(scheme.parse("26.10.04").unwrap(), version);
+
+    let scheme = CalVer::new("0Y.0M.MICRO").unwrap();
+    let mut version = scheme.parse("26.09.4-rc.1").unwrap();
+    scheme.increment(&mut version, &CommitType::Patch, october_third);
+    assert_eq!(scheme.format(&version), "26.10.0");
+    assert_eq!(scheme.format(&version.next_pre_release("rc", &[])), "26.10.0-rc.1");
+
+    // 1999-12-31 23:00:00 UTC
+    let scheme = CalVer::new("0Y.0M.MICRO").unwrap();
+    let mut version = SemanticVersion::new();
+    scheme.increment(&mut version, &CommitType::Patch, 946681200);
+    assert_eq!(scheme.format(&version), "99.12.0");
+
+    assert!(CalVer::new("YYYY.MICRO.MM").is_err());
+    assert!(CalVer::new("YYYY.MM").is_err());
+    assert!(CalVer::new("YYYY.WW.MICRO").is_err());
+    assert!(from_config("datever", None, true).is_err());
+    assert_eq!(from_config("SemVer", None, true).unwrap().name(), "semver");
+}
+
+#[test]
+fn test_semver_initial_development()
+{
+    let cases = [
+        ("0.3.2", CommitType::Major, true, "0.4.0"),
+        ("0.3.2", CommitType::Minor, true, "0.3.3"),
+        ("0.3.2", CommitType::Patch, true, "0.3.3"),
+        ("0.3.2", CommitType::Major, false, "1.0.0"),
+        ("0.3.2", CommitType::Minor, false, "0.4.0"),
+        ("1.3.2", CommitType::Major, true, "2.0.0"),
+        ("1.3.2", CommitType::Minor, true, "1.4.0"),
+    ];
+
+    for (version, commit_type, initial_development, expected) in cases.iter()
+    {
+        let scheme = SemVer { initial_development: *initial_development };
+        let mut version = SemanticVersion::parse(version).unwrap();
+        scheme.increment(&mut version, commit_type, 0);
+        assert_eq!(version.to_string(), *expected, "{:?} [initial development: {}]", commit_type, initial_development);
+    }
+}
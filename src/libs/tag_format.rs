@@ -0,0 +1,248 @@
+use std::{fmt::Display, rc::Rc};
+
+use super::{scheme::{SemVer, VersioningScheme}, version::{SemanticVersion, VersionParseError}};
+
+/// Template used to render the tag names of new releases and to recognise the tags of previous ones.
+///
+/// Supported placeholders:
+/// - `{version}`: the full version, e.g. `1.2.3-rc.1+build.5`
+/// - `{major}`, `{minor}`, `{patch}`
+/// - `{prerelease}`, `{-prerelease}`: the pre-release identifiers, the latter prefixed with `-` when there are any
+/// - `{build}`, `{+build}`: the build metadata, the latter prefixed with `+` when there is any
+///
+/// ```text
+/// v{major}.{minor}.{patch}{-prerelease}  ->  v1.3.0-rc.1
+/// mylib@{version}                        ->  mylib@1.3.0-rc.1
+/// ```
+///
+/// `{version}` is rendered by the versioning scheme, e.g. zero padded for CalVer; the other placeholders are plain numbers.
+///
+/// Optional include and exclude regexes limit which tags are recognised, e.g. to ignore `docs-2024` or `release_17`.
+#[derive(Debug, Clone)]
+pub struct TagFormat
+{
+    template: String,
+    regex: regex::Regex,
+    scheme: Rc<dyn VersioningScheme>,
+    include: Option<regex::Regex>,
+    exclude: Option<regex::Regex>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TagFormatError
+{
+    /// The template uses an unknown or duplicated placeholder, or does not contain a version at all.
+    InvalidTemplate(String),
+    /// The tag does not follow the template, e.g. `docs-latest`.
+    Mismatch(String),
+    /// The tag follows the template, but the version in it is not valid.
+    InvalidVersion(String, VersionParseError),
+    /// The include or exclude filter is not a valid regex.
+    InvalidFilter(String),
+    /// The tag is not included or is excluded by the filters.
+    Filtered(String),
+}
+
+impl Display for TagFormatError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            TagFormatError::InvalidTemplate(reason) => write!(f, "invalid tag format: {}", reason),
+            TagFormatError::Mismatch(tag) => write!(f, "`{}` does not follow the tag format", tag),
+            TagFormatError::InvalidVersion(tag, error) => write!(f, "`{}` does not contain a valid version: {}", tag, error),
+            TagFormatError::InvalidFilter(reason) => write!(f, "invalid tag filter: {}", reason),
+            TagFormatError::Filtered(tag) => write!(f, "`{}` is filtered out by the tag filters", tag),
+        }
+    }
+}
+
+impl std::error::Error for TagFormatError {}
+
+impl TagFormat
+{
+    pub const DEFAULT: &'static str = "{version}";
+
+    pub fn new(template: &str) -> Result<TagFormat, TagFormatError>
+    {
+        let mut pattern = String::from("^");
+        let mut rest = template;
+        while let Some(start) = rest.find('{')
+        {
+            pattern.push_str(&regex::escape(&rest[..start]));
+
+            let end = rest[start..].find('}')
+                .ok_or_else(|| TagFormatError::InvalidTemplate(format!("unclosed placeholder in `{}`", template)))?;
+            let placeholder = &rest[start..start + end + 1];
+            pattern.push_str(match placeholder
+            {
+                "{version}" => r"(?P<version>[0-9]+\.[0-9]+\.[0-9]+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)",
+                "{major}" => r"(?P<major>[0-9]+)",
+                "{minor}" => r"(?P<minor>[0-9]+)",
+                "{patch}" => r"(?P<patch>[0-9]+)",
+                "{prerelease}" => r"(?P<prerelease>[0-9A-Za-z.-]*)",
+                "{-prerelease}" => r"(?:-(?P<prerelease>[0-9A-Za-z.-]+))?",
+                "{build}" => r"(?P<build>[0-9A-Za-z.-]*)",
+                "{+build}" => r"(?:\+(?P<build>[0-9A-Za-z.-]+))?",
+                _ => return Err(TagFormatError::InvalidTemplate(format!("unknown placeholder `{}`", placeholder))),
+            });
+
+            rest = &rest[start + end + 1..];
+        }
+        pattern.push_str(&regex::escape(rest));
+        pattern.push('$');
+
+        // Duplicated placeholders end up as duplicated capture group names.
+        let regex = regex::Regex::new(&pattern)
+            .map_err(|error| TagFormatError::InvalidTemplate(error.to_string()))?;
+
+        let names = regex.capture_names().flatten().collect::<Vec<&str>>();
+        let has_version = names.contains(&"version");
+        let has_core = names.contains(&"major") && names.contains(&"minor") && names.contains(&"patch");
+        if has_version == has_core
+        {
+            return Err(TagFormatError::InvalidTemplate(format!("`{}` needs either {{version}} or {{major}}, {{minor}} and {{patch}}", template)));
+        }
+
+        Ok(TagFormat { template: template.to_string(), regex, scheme: Rc::new(SemVer::default()), include: None, exclude: None })
+    }
+
+    /// The format of repositories that do not configure one: `v{version}` when the highest version tag has a `v` prefix,
+    /// like the tags of releases made before tag formats were configurable, and `{version}` otherwise.
+    pub fn detect(tag_names: &[String], scheme: Rc<dyn VersioningScheme>) -> TagFormat
+    {
+        let highest = |template: &str| {
+            let format = TagFormat::new(template).unwrap().with_scheme(scheme.clone());
+            let version = tag_names.iter().filter_map(|x| format.parse(x).ok()).max();
+            (format, version)
+        };
+
+        let (plain, plain_version) = highest(TagFormat::DEFAULT);
+        let (prefixed, prefixed_version) = highest("v{version}");
+        if prefixed_version > plain_version { prefixed } else { plain }
+    }
+
+    pub fn template(&self) -> &str { &self.template }
+
+    pub fn with_scheme(mut self, scheme: Rc<dyn VersioningScheme>) -> TagFormat
+    {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Only recognises tags matching `include`, if any, and not matching `exclude`, if any.
+    pub fn with_filters(mut self, include: Option<&str>, exclude: Option<&str>) -> Result<TagFormat, TagFormatError>
+    {
+        let filter = |pattern: &str| regex::Regex::new(pattern)
+            .map_err(|error| TagFormatError::InvalidFilter(error.to_string()));
+        self.include = include.map(filter).transpose()?;
+        self.exclude = exclude.map(filter).transpose()?;
+        Ok(self)
+    }
+
+    /// Renders the tag name for the version.
+    pub fn render(&self, version: &SemanticVersion) -> String
+    {
+        let pre_release = version.get_pre_release().iter().map(|x| x.to_string()).collect::<Vec<String>>().join(".");
+        let build = version.get_build().join(".");
+
+        let mut tag = self.template.clone();
+
+        // Keep the x[.x[.x]] releases of `keep_*_up_to_date` short, like `SemanticVersion` does.
+        if version.get_minor() == u32::MAX
+        {
+            tag = tag.replace(".{minor}", "").replace("{minor}", "");
+        }
+        if version.get_patch() == u32::MAX
+        {
+            tag = tag.replace(".{patch}", "").replace("{patch}", "");
+        }
+
+        tag.replace("{version}", &self.scheme.format(version))
+            .replace("{major}", &version.get_major().to_string())
+            .replace("{minor}", &version.get_minor().to_string())
+            .replace("{patch}", &version.get_patch().to_string())
+            .replace("{-prerelease}", &if pre_release.is_empty() { String::new() } else { format!("-{}", pre_release) })
+            .replace("{prerelease}", &pre_release)
+            .replace("{+build}", &if build.is_empty() { String::new() } else { format!("+{}", build) })
+            .replace("{build}", &build)
+    }
+
+    /// Recognises a tag name rendered by this format and returns its version.
+    ///
+    /// Versions are parsed leniently, so `v01.2.3` is recognised as 1.2.3 and would be rendered as `v1.2.3`.
+    pub fn parse(&self, tag: &str) -> Result<SemanticVersion, TagFormatError>
+    {
+        if self.include.as_ref().is_some_and(|x| !x.is_match(tag)) || self.exclude.as_ref().is_some_and(|x| x.is_match(tag))
+        {
+            return Err(TagFormatError::Filtered(tag.to_string()));
+        }
+
+        let captures = self.regex.captures(tag)
+            .ok_or_else(|| TagFormatError::Mismatch(tag.to_string()))?;
+
+        let version = match captures.name("version")
+        {
+            Some(version) => version.as_str().to_string(),
+            None => {
+                let mut version = format!("{}.{}.{}", &captures["major"], &captures["minor"], &captures["patch"]);
+                if let Some(pre_release) = captures.name("prerelease").filter(|x| !x.as_str().is_empty())
+                {
+                    version = format!("{}-{}", version, pre_release.as_str());
+                }
+                if let Some(build) = captures.name("build").filter(|x| !x.as_str().is_empty())
+                {
+                    version = format!("{}+{}", version, build.as_str());
+                }
+                version
+            }
+        };
+
+        self.scheme.parse(&version)
+            .map_err(|error| TagFormatError::InvalidVersion(tag.to_string(), error))
+    }
+}
+
+#[test]
+fn test_tag_format()
+{
+    let cases = [
+        ("{version}", "1.3.0-rc.1+build.5"),
+        ("v{version}", "v1.3.0"),
+        ("mylib@{version}", "mylib@1.3.0-rc.1"),
+        ("my-lib-v{version}", "my-lib-v1.2.3"),
+        ("v{version}", "v1.4.0-rc.2+build.77"),
+        ("v{major}.{minor}.{patch}{-prerelease}", "v1.3.0-rc.1"),
+        ("v{major}.{minor}.{patch}{-prerelease}", "v1.3.0"),
+        ("release_{major}_{minor}_{patch}{+build}", "release_1_3_0+77"),
+    ];
+
+    for (template, tag) in cases.iter()
+    {
+        let format = TagFormat::new(template).unwrap();
+        let version = format.parse(tag).unwrap_or_else(|error| panic!("{} - {}", template, error));
+        assert_eq!(format.render(&version), *tag, "Tag did not round-trip: {}", template);
+    }
+
+    let format = TagFormat::new("my-lib-v{version}").unwrap();
+    assert!(matches!(format.parse("docs-latest"), Err(TagFormatError::Mismatch(_))));
+    assert!(matches!(format.parse("my-lib-v1.2"), Err(TagFormatError::Mismatch(_))));
+    assert_eq!(format.render(&SemanticVersion::from(1, u32::MAX, u32::MAX)), "my-lib-v1");
+    // Leading zeros are tolerated, but rendered without.
+    assert_eq!(format.render(&format.parse("my-lib-v01.2.3").unwrap()), "my-lib-v1.2.3");
+    assert_eq!(TagFormat::new("v{major}.{minor}.{patch}").unwrap().render(&SemanticVersion::from(1, 2, u32::MAX)), "v1.2");
+
+    let format = TagFormat::new("{version}").unwrap().with_filters(Some(r"^[0-9]"), Some(r"-(nightly|dev)")).unwrap();
+    assert!(format.parse("1.2.0").is_ok());
+    assert!(matches!(format.parse("1.3.0-nightly.4"), Err(TagFormatError::Filtered(_))));
+    assert!(TagFormat::new("{version}").unwrap().with_filters(Some("(unclosed"), None).is_err());
+
+    let detect = |tags: &[&str]| TagFormat::detect(&tags.iter().map(|x| x.to_string()).collect::<Vec<String>>(), Rc::new(SemVer::default())).template().to_string();
+    assert_eq!(detect(&[]), "{version}");
+    assert_eq!(detect(&["v1.2.0", "docs-latest"]), "v{version}");
+    assert_eq!(detect(&["v1.2.0", "1.3.0"]), "{version}");
+
+    assert!(TagFormat::new("v{major}").is_err());
+    assert!(TagFormat::new("{version}-{version}").is_err());
+    assert!(TagFormat::new("{name}@{version}").is_err());
+}
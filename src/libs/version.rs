@@ -1,17 +1,73 @@
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display, hash::{Hash, Hasher}, str::FromStr};
 
 use log::debug;
 
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommitType
 {
     Major,
     Minor,
     Patch,
+    /// Listed in the release notes at most, e.g. `docs:` or `ci:`; never bumps the version.
+    None,
 }
 
-#[derive(serde::Deserialize, Debug, Clone, Default)]
+impl CommitType
+{
+    /// Order of the commit types by their bump, the highest first.
+    pub fn rank(self) -> u8
+    {
+        match self
+        {
+            CommitType::Major => 0,
+            CommitType::Minor => 1,
+            CommitType::Patch => 2,
+            CommitType::None => 3,
+        }
+    }
+}
+
+/// A single dot-separated pre-release identifier, see <https://semver.org/#spec-item-9>.
+///
+/// The variant order matters for precedence: numeric identifiers always have lower precedence than alphanumeric ones.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Identifier
+{
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl Identifier
+{
+    pub fn parse(identifier: &str) -> Identifier
+    {
+        // Parsed versions never contain numeric identifiers with leading zeros; anything else, e.g. a channel name, is kept as text.
+        let is_numeric = !identifier.is_empty()
+            && identifier.chars().all(|c| c.is_ascii_digit())
+            && (identifier == "0" || !identifier.starts_with('0'));
+
+        match identifier.parse::<u64>()
+        {
+            Ok(value) if is_numeric => Identifier::Numeric(value),
+            _ => Identifier::AlphaNumeric(identifier.to_string()),
+        }
+    }
+}
+
+impl Display for Identifier
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Identifier::Numeric(value) => write!(f, "{}", value),
+            Identifier::AlphaNumeric(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A version as defined by <https://semver.org/>; (de)serialized as its string form, e.g. `"1.3.0-rc.1"`.
+#[derive(Debug, Clone, Default)]
 pub struct SemanticVersion
 {
     major: u32,
@@ -22,16 +78,82 @@ pub struct SemanticVersion
     delta_minor: u32,
     delta_patch: u32,
 
-    // Prefix & Suffix
-    prefix: Option<String>,
-    suffix: Option<String>,
+    // x.x.x[-pre.release][+build.metadata]
+    pre_release: Vec<Identifier>,
+    build: Vec<String>,
 }
 
 impl PartialEq for SemanticVersion
 {
     fn eq(&self, other: &Self) -> bool
     {
-        self.major == other.major && self.minor == other.minor && self.patch == other.patch
+        // Build metadata does not take part in equality, see https://semver.org/#spec-item-10
+        self.major == other.major && self.minor == other.minor && self.patch == other.patch && self.pre_release == other.pre_release
+    }
+}
+
+impl Eq for SemanticVersion {}
+
+impl serde::Serialize for SemanticVersion
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SemanticVersion
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let version = String::deserialize(deserializer)?;
+        SemanticVersion::parse(&version).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Hash for SemanticVersion
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        // Must agree with `PartialEq`, so the build metadata is left out here as well.
+        self.major.hash(state);
+        self.minor.hash(state);
+        self.patch.hash(state);
+        self.pre_release.hash(state);
+    }
+}
+
+impl PartialOrd for SemanticVersion
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemanticVersion
+{
+    /// Precedence as defined by <https://semver.org/#spec-item-11>.
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        self.major.cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+            .then_with(|| compare_pre_release(&self.pre_release, &other.pre_release))
+    }
+}
+
+/// Precedence of two sets of pre-release identifiers, where no identifiers at all means a normal version.
+pub fn compare_pre_release(left: &[Identifier], right: &[Identifier]) -> Ordering
+{
+    match (left.is_empty(), right.is_empty())
+    {
+        // A pre-release version has lower precedence than the normal version.
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        // Identifiers are compared left to right; a larger set of identifiers wins when all preceding ones are equal.
+        (false, false) => left.cmp(right),
     }
 }
 
@@ -40,18 +162,22 @@ impl SemanticVersion
     // Ctor
     pub fn new() -> SemanticVersion
     {
-        SemanticVersion { major: 0, minor: 0, patch: 0, delta_major: 0, delta_minor: 0, delta_patch: 0, prefix: None, suffix: None }
+        SemanticVersion::default()
     }
 
     pub fn from(major: u32, minor: u32, patch: u32) -> SemanticVersion
     {
-        SemanticVersion { major, minor, patch, delta_major: 0, delta_minor: 0, delta_patch: 0, prefix: None, suffix: None }
+        SemanticVersion { major, minor, patch, ..Default::default() }
     }
 
     // getters
     pub fn get_major(&self) -> u32 { self.major }
     pub fn get_minor(&self) -> u32 { self.minor }
     pub fn get_patch(&self) -> u32 { self.patch }
+    pub fn get_pre_release(&self) -> &[Identifier] { &self.pre_release }
+    pub fn get_build(&self) -> &[String] { &self.build }
+
+    pub fn is_pre_release(&self) -> bool { !self.pre_release.is_empty() }
 
     // Increment
     pub fn increment(&mut self, commit_type: &CommitType)
@@ -60,12 +186,60 @@ impl SemanticVersion
     }
     pub fn increment_by(&mut self, commit_type: &CommitType, value: u32)
     {
-        match commit_type
+        // A pre-release already is the upcoming version; `1.3.0-rc.1` becomes `1.3.0` and not `1.3.1`, unless the change is bigger than that.
+        let value = match commit_type
+        {
+            // Nothing to bump, the version stays as it is, pre-release and all.
+            _ if value == 0 => return,
+            CommitType::Major if self.is_pre_release() && self.minor == 0 && self.patch == 0 => value - 1,
+            CommitType::Minor if self.is_pre_release() && self.patch == 0 => value - 1,
+            CommitType::Patch if self.is_pre_release() => value - 1,
+            CommitType::None => return,
+            _ => value,
+        };
+
+        if value > 0
         {
-            CommitType::Major => { self.major += value; self.delta_major; self.minor = 0; self.delta_minor = 0; self.patch = 0; },
-            CommitType::Minor => { self.minor += value; self.delta_major += value; self.patch = 0; self.delta_patch = 0; },
-            CommitType::Patch => { self.patch += value; self.delta_patch += value; },
+            match commit_type
+            {
+                CommitType::Major => { self.major += value; self.delta_major += value; self.minor = 0; self.delta_minor = 0; self.patch = 0; self.delta_patch = 0; },
+                CommitType::Minor => { self.minor += value; self.delta_minor += value; self.patch = 0; self.delta_patch = 0; },
+                CommitType::Patch => { self.patch += value; self.delta_patch += value; },
+                CommitType::None => {},
+            }
         }
+
+        // A bumped version is a new normal version; pre-release and build belong to the old one.
+        self.pre_release.clear();
+        self.build.clear();
+    }
+
+    pub fn reset_delta(&mut self)
+    {
+        self.delta_major = 0;
+        self.delta_minor = 0;
+        self.delta_patch = 0;
+    }
+
+    /// The next pre-release of this version on the channel, counting on from the existing versions.
+    ///
+    /// `1.3.0` on the `rc` channel becomes `1.3.0-rc.1`, or `1.3.0-rc.3` if `1.3.0-rc.2` already exists.
+    pub fn next_pre_release(&self, channel: &str, existing: &[SemanticVersion]) -> SemanticVersion
+    {
+        let counter = existing.iter()
+            .chain(std::iter::once(self))
+            .filter(|x| x.major == self.major && x.minor == self.minor && x.patch == self.patch)
+            .filter_map(|x| match x.pre_release.as_slice()
+            {
+                [Identifier::AlphaNumeric(name), Identifier::Numeric(counter)] if name == channel => Some(*counter),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let mut version = SemanticVersion::from(self.major, self.minor, self.patch);
+        version.pre_release = vec![Identifier::parse(channel), Identifier::Numeric(counter + 1)];
+        version
     }
 
     pub fn get_delta_major(&self) -> u32 { self.delta_major }
@@ -73,52 +247,201 @@ impl SemanticVersion
     pub fn get_delta_patch(&self) -> u32 { self.delta_patch }
 
     // Parse
-    pub fn parse(version: &str) -> SemanticVersion
+    /// Lenient parsing, meant for tag names: accepts a `v` prefix and leading zeros.
+    ///
+    /// Neither survives rendering, `v01.2.3` is displayed as `1.2.3`; other prefixes belong to the [`TagFormat`](super::tag_format::TagFormat).
+    pub fn parse(version: &str) -> Result<SemanticVersion, VersionParseError>
+    {
+        SemanticVersion::parse_with(version, ParseMode::Lenient)
+    }
+
+    /// Strict parsing, exactly as defined by <https://semver.org/#backusnaur-form-grammar-for-valid-semver-versions>.
+    pub fn parse_strict(version: &str) -> Result<SemanticVersion, VersionParseError>
+    {
+        SemanticVersion::parse_with(version, ParseMode::Strict)
+    }
+
+    pub fn parse_with(version: &str, mode: ParseMode) -> Result<SemanticVersion, VersionParseError>
+    {
+        debug!("Parsing version: {} [{:?}]", version, mode);
+
+        // [v]x.x.x[-pre.release][+build.metadata]
+        let version = match version.strip_prefix(['v', 'V'])
+        {
+            Some(stripped) if mode == ParseMode::Lenient => stripped,
+            _ => version,
+        };
+
+        let (version, build) = match version.split_once('+')
+        {
+            Some((version, build)) => (version, parse_identifiers(build, VersionComponent::Build)?),
+            None => (version, Vec::new()),
+        };
+
+        let (core, pre_release) = match version.split_once('-')
+        {
+            Some((core, pre_release)) => (core, parse_pre_release(pre_release)?),
+            None => (version, Vec::new()),
+        };
+
+        let mut parts = core.split('.');
+        let major = parse_number(parts.next(), VersionComponent::Major, mode)?;
+        let minor = parse_number(parts.next(), VersionComponent::Minor, mode)?;
+        let patch = parse_number(parts.next(), VersionComponent::Patch, mode)?;
+        if let Some(part) = parts.next()
+        {
+            return Err(VersionParseError::Unexpected(VersionComponent::Patch, part.to_string()));
+        }
+
+        Ok(SemanticVersion { major, minor, patch, pre_release, build, ..Default::default() })
+    }
+}
+
+impl FromStr for SemanticVersion
+{
+    type Err = VersionParseError;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err>
     {
-        debug!("Parsing version: {}", version);
+        SemanticVersion::parse_strict(version)
+    }
+}
+
+impl TryFrom<&str> for SemanticVersion
+{
+    type Error = VersionParseError;
+
+    fn try_from(version: &str) -> Result<Self, Self::Error>
+    {
+        SemanticVersion::parse_strict(version)
+    }
+}
 
-        let mut major = 0;
-        let mut minor = 0;
-        let mut patch = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode
+{
+    /// Exactly the semver.org grammar.
+    Strict,
+    /// Additionally accepts a `v` prefix and leading zeros on the major, minor and patch versions.
+    Lenient,
+}
 
-        let parts = version.split('-').collect::<Vec<&str>>();
-        let version_part_index = if parts.len() > 1 { 1 } else { 0 };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionComponent
+{
+    Major,
+    Minor,
+    Patch,
+    PreRelease,
+    Build,
+}
 
-        let version_parts = parts[version_part_index].split('.').collect::<Vec<&str>>();
-        if !version_parts.is_empty()
+impl Display for VersionComponent
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
         {
-            // Remove any characters on major part and only leave digits.
-            let major_part = version_parts[0].chars().filter(|c| c.is_digit(10)).collect::<String>();
-            debug!("Major: {} [{}]", major_part, version_parts[0]);
-            major = major_part.parse::<u32>().unwrap();
+            VersionComponent::Major => write!(f, "major"),
+            VersionComponent::Minor => write!(f, "minor"),
+            VersionComponent::Patch => write!(f, "patch"),
+            VersionComponent::PreRelease => write!(f, "pre-release"),
+            VersionComponent::Build => write!(f, "build metadata"),
         }
-        if version_parts.len() > 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionParseError
+{
+    /// The component is not there at all, e.g. the patch of `1.2`.
+    Missing(VersionComponent),
+    /// The component or one of its identifiers is empty, e.g. `1..2` or `1.2.3-`.
+    Empty(VersionComponent),
+    /// The component contains characters that are not allowed, e.g. the minor of `v2.x`.
+    InvalidCharacter(VersionComponent, String),
+    /// A numeric component with leading zeros, only rejected in strict mode.
+    LeadingZero(VersionComponent, String),
+    /// A numeric component that does not fit into 32 bits.
+    Overflow(VersionComponent, String),
+    /// Anything that follows a complete component, e.g. the `.4` of `1.2.3.4`.
+    Unexpected(VersionComponent, String),
+}
+
+impl Display for VersionParseError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
         {
-            // Remove any characters on minor part.
-            let minor_part = version_parts[1].chars().filter(|c| c.is_digit(10)).collect::<String>();
-            debug!("Minor: {} [{}]", minor_part, version_parts[1]);
-            minor = minor_part.parse::<u32>().unwrap();
+            VersionParseError::Missing(component) => write!(f, "missing {} version", component),
+            VersionParseError::Empty(component) => write!(f, "empty {} identifier", component),
+            VersionParseError::InvalidCharacter(component, value) => write!(f, "invalid character in {} `{}`", component, value),
+            VersionParseError::LeadingZero(component, value) => write!(f, "leading zero in {} `{}`", component, value),
+            VersionParseError::Overflow(component, value) => write!(f, "{} `{}` is too large", component, value),
+            VersionParseError::Unexpected(component, value) => write!(f, "unexpected `{}` after {}", value, component),
         }
-        if version_parts.len() > 2
+    }
+}
+
+impl std::error::Error for VersionParseError {}
+
+fn parse_number(part: Option<&str>, component: VersionComponent, mode: ParseMode) -> Result<u32, VersionParseError>
+{
+    let part = part.ok_or(VersionParseError::Missing(component))?;
+
+    if part.is_empty()
+    {
+        return Err(VersionParseError::Empty(component));
+    }
+    if !part.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(VersionParseError::InvalidCharacter(component, part.to_string()));
+    }
+    if mode == ParseMode::Strict && part.len() > 1 && part.starts_with('0')
+    {
+        return Err(VersionParseError::LeadingZero(component, part.to_string()));
+    }
+
+    part.parse::<u32>().map_err(|_| VersionParseError::Overflow(component, part.to_string()))
+}
+
+/// The dot-separated pre-release identifiers, e.g. `rc.1`, validated like those of a version.
+pub fn parse_pre_release(pre_release: &str) -> Result<Vec<Identifier>, VersionParseError>
+{
+    Ok(parse_identifiers(pre_release, VersionComponent::PreRelease)?.iter().map(|x| Identifier::parse(x)).collect())
+}
+
+fn parse_identifiers(identifiers: &str, component: VersionComponent) -> Result<Vec<String>, VersionParseError>
+{
+    let mut result = Vec::new();
+    for identifier in identifiers.split('.')
+    {
+        if identifier.is_empty()
         {
-            // Remove any characters on patch part.
-            let patch_part = version_parts[2].chars().filter(|c| c.is_digit(10)).collect::<String>();
-            debug!("Patch: {} [{}]", patch_part, version_parts[2]);
-            patch = patch_part.parse::<u32>().unwrap();
+            return Err(VersionParseError::Empty(component));
+        }
+        if !identifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(VersionParseError::InvalidCharacter(component, identifier.to_string()));
+        }
+        // Only numeric pre-release identifiers are affected, build metadata may have leading zeros.
+        // Not even lenient parsing accepts them, `01` would no longer compare as a number.
+        let is_numeric = identifier.chars().all(|c| c.is_ascii_digit());
+        if component == VersionComponent::PreRelease && is_numeric && identifier.len() > 1 && identifier.starts_with('0')
+        {
+            return Err(VersionParseError::LeadingZero(component, identifier.to_string()));
         }
 
-        let prefix = if parts.len() > 1 { Some(parts[0].to_string()) } else { None };
-        let suffix = if parts.len() > 2 { Some(parts[2].to_string()) } else { None };
-        
-        SemanticVersion { major, minor, patch, delta_major: 0, delta_minor: 0, delta_patch: 0, prefix, suffix }
+        result.push(identifier.to_string());
     }
+
+    Ok(result)
 }
 
 impl Display for SemanticVersion
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut version = self.major.to_string();
-        
+
         // x[.x] or x[.x[.x]]; Optional minor and patch parts.
         if self.minor != u32::MAX
         {
@@ -129,16 +452,143 @@ impl Display for SemanticVersion
             version = format!("{}.{}", version, self.patch);
         }
 
-        // [prefix-]x.x.x
-        if let Some(prefix) = &self.prefix
+        // x.x.x[-pre.release]
+        if !self.pre_release.is_empty()
         {
-            version = format!("{}-{}", prefix, version);
+            let pre_release = self.pre_release.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+            version = format!("{}-{}", version, pre_release.join("."));
         }
-        // [prefix-]x.x.x[-suffix]
-        if let Some(suffix) = &self.suffix
+        // x.x.x[-pre.release][+build.metadata]
+        if !self.build.is_empty()
         {
-            version = format!("{}-{}", version, suffix);
+            version = format!("{}+{}", version, self.build.join("."));
         }
         write!(f, "{}", version)
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse()
+{
+    let versions = [
+        "1.0.0",
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-0.3.7",
+        "1.0.0-x.7.z.92",
+        "1.0.0-x-y-z.--",
+        "1.0.0-alpha+001",
+        "1.0.0+20130313144700",
+        "1.0.0-beta+exp.sha.5114f85",
+        "1.0.0+21AF26D3----117B344092BD",
+        "1.4.0-rc.2+build.77",
+    ];
+
+    for version in versions.iter()
+    {
+        assert_eq!(SemanticVersion::parse(version).unwrap().to_string(), *version, "Version did not round-trip: {}", version);
+    }
+
+    let version = SemanticVersion::parse("v1.4.0-rc.2+build.77").unwrap();
+    assert_eq!((version.get_major(), version.get_minor(), version.get_patch()), (1, 4, 0));
+    assert_eq!(version.get_pre_release(), [Identifier::AlphaNumeric("rc".to_string()), Identifier::Numeric(2)]);
+    assert_eq!(version.get_build(), ["build".to_string(), "77".to_string()]);
+
+    let mut version = SemanticVersion::parse("1.0.0-alpha.1+build.1").unwrap();
+    version.increment(&CommitType::Patch);
+    assert_eq!(version.to_string(), "1.0.0");
+}
+
+#[test]
+fn test_precedence()
+{
+    // Ascending precedence, taken from https://semver.org/#spec-item-11
+    let versions = [
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+        "1.0.1",
+        "1.1.0",
+        "2.0.0",
+        "2.1.1",
+    ];
+
+    for pair in versions.windows(2)
+    {
+        assert!(SemanticVersion::parse(pair[0]).unwrap() < SemanticVersion::parse(pair[1]).unwrap(), "{} should be lower than {}", pair[0], pair[1]);
+    }
+
+    let mut shuffled = versions.iter().rev().map(|x| SemanticVersion::parse(x).unwrap()).collect::<Vec<SemanticVersion>>();
+    shuffled.sort();
+    assert_eq!(shuffled.iter().map(|x| x.to_string()).collect::<Vec<String>>(), versions);
+
+    // Build metadata is ignored for precedence and hashing.
+    let with_build = SemanticVersion::parse("1.0.0+build.1").unwrap();
+    let without_build = SemanticVersion::parse("1.0.0").unwrap();
+    assert_eq!(with_build.cmp(&without_build), Ordering::Equal);
+    assert_eq!(std::collections::HashSet::from([with_build, without_build]).len(), 1);
+}
+
+#[test]
+fn test_parse_errors()
+{
+    use VersionComponent::*;
+
+    assert_eq!(SemanticVersion::parse("docs-latest"), Err(VersionParseError::InvalidCharacter(Major, "docs".to_string())));
+    assert_eq!(SemanticVersion::parse("v2.x"), Err(VersionParseError::InvalidCharacter(Minor, "x".to_string())));
+    assert_eq!(SemanticVersion::parse("1.2"), Err(VersionParseError::Missing(Patch)));
+    assert_eq!(SemanticVersion::parse("1.2.3.4"), Err(VersionParseError::Unexpected(Patch, "4".to_string())));
+    assert_eq!(SemanticVersion::parse("1.2.3-"), Err(VersionParseError::Empty(PreRelease)));
+    assert_eq!(SemanticVersion::parse("1.2.3+a_b"), Err(VersionParseError::InvalidCharacter(Build, "a_b".to_string())));
+    assert_eq!(SemanticVersion::parse("1.2.99999999999"), Err(VersionParseError::Overflow(Patch, "99999999999".to_string())));
+
+    // Strict mode rejects what lenient mode tolerates.
+    assert_eq!(SemanticVersion::parse_strict("v1.2.3"), Err(VersionParseError::InvalidCharacter(Major, "v1".to_string())));
+    assert_eq!(SemanticVersion::parse_strict("01.2.3"), Err(VersionParseError::LeadingZero(Major, "01".to_string())));
+    assert_eq!(SemanticVersion::parse_strict("1.2.3-rc.01"), Err(VersionParseError::LeadingZero(PreRelease, "01".to_string())));
+    assert_eq!(SemanticVersion::parse("01.2.3").unwrap(), SemanticVersion::from(1, 2, 3));
+    assert_eq!(SemanticVersion::parse("v01.2.3").unwrap().to_string(), "1.2.3");
+    assert_eq!(SemanticVersion::parse("1.0.0-01"), Err(VersionParseError::LeadingZero(PreRelease, "01".to_string())));
+    assert_eq!(SemanticVersion::parse("1.0.0-rc.0+001").unwrap().to_string(), "1.0.0-rc.0+001");
+    assert_eq!("1.2.3".parse::<SemanticVersion>().unwrap(), SemanticVersion::from(1, 2, 3));
+    assert!(SemanticVersion::try_from("v1.2.3").is_err());
+}
+
+#[test]
+fn test_pre_release()
+{
+    let existing = ["1.2.0", "1.3.0-rc.1", "1.3.0-rc.2", "1.3.0-beta.7", "1.4.0-rc.9"].iter()
+        .map(|x| SemanticVersion::parse(x).unwrap())
+        .collect::<Vec<SemanticVersion>>();
+
+    let version = SemanticVersion::from(1, 3, 0);
+    assert_eq!(version.next_pre_release("rc", &existing).to_string(), "1.3.0-rc.3");
+    assert_eq!(version.next_pre_release("beta", &existing).to_string(), "1.3.0-beta.8");
+    assert_eq!(version.next_pre_release("alpha", &existing).to_string(), "1.3.0-alpha.1");
+
+    // Incrementing a pre-release does not burn the version it is a pre-release of.
+    let cases = [
+        ("1.3.0-rc.2", CommitType::Patch, "1.3.0"),
+        ("1.3.0-rc.2", CommitType::Minor, "1.3.0"),
+        ("1.3.0-rc.2", CommitType::Major, "2.0.0"),
+        ("1.3.1-rc.2", CommitType::Patch, "1.3.1"),
+        ("1.3.1-rc.2", CommitType::Minor, "1.4.0"),
+        ("2.0.0-rc.2", CommitType::Major, "2.0.0"),
+        ("1.3.0-rc.2", CommitType::None, "1.3.0-rc.2"),
+    ];
+    for (version, commit_type, expected) in cases.iter()
+    {
+        let mut version = SemanticVersion::parse(version).unwrap();
+        version.increment(commit_type);
+        assert_eq!(version.to_string(), *expected, "{:?}", commit_type);
+    }
+
+    let mut version = SemanticVersion::parse("1.3.0-rc.2").unwrap();
+    version.increment_by(&CommitType::Patch, 0);
+    assert_eq!(version.to_string(), "1.3.0-rc.2");
+}
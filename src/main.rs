@@ -57,6 +57,36 @@
 //! 
 //! # Override the repository type: github, gitlab, bitbucket, gitea, etc.
 //! semver --input-file .semver.json --repository . --override-repository-type gitea
+//! 
+//! # Write the planned releases as JSON, see `Release` for the schema; `-` writes to stdout.
+//! semver --input-file .semver.json --repository . --dry-run --output-json -
+//! 
+//! # Leave the initial development behind and release 1.0.0 from the latest commit.
+//! semver --input-file .semver.json --repository . --graduate
+//! 
+//! # Only consider tags with versions matching the requirement: ^1.2, ~1.2.3, >=1.0, <2.0, 1.x, etc.
+//! semver --input-file .semver.json --repository . --only-tags ">=2.0"
+//! 
+//! # Ignore tags that are not releases, overriding `include` and `exclude` of the configuration.
+//! semver --input-file .semver.json --repository . --tag-exclude "^(docs|deploy)-"
+//! 
+//! # Adopt the tool on an untagged history: treat the commit as released with the version, <ref>=<version>.
+//! semver --input-file .semver.json --repository . --baseline "v2-final=2.3.0"
+//! 
+//! # Analyse any range of commits, without checking it out; --from is exclusive, --to defaults to HEAD.
+//! # Dry-runs over a range report its releases even when they are tagged already.
+//! semver --input-file .semver.json --repository . --dry-run --from 1.2.0 --to 1.3.0
+//! 
+//! # Name the branch of a detached checkout; otherwise the checked out branch or, when detached, GITHUB_HEAD_REF,
+//! # GITHUB_REF_NAME, CI_COMMIT_REF_NAME, BRANCH_NAME (unless they name a tag) and the remote branch containing HEAD
+//! # are tried in that order.
+//! semver --input-file .semver.json --repository . --branch main
+//! 
+//! # Shallow clones without a version tag in their history are an error, unless more history may be fetched from origin.
+//! semver --input-file .semver.json --repository . --deepen
+//! 
+//! # Check a commit message against Conventional Commits, e.g. from a commit-msg hook; `-` reads stdin.
+//! semver --lint .git/COMMIT_EDITMSG
 //! ```
 //! 
 //! ## Configuration
@@ -64,6 +94,9 @@
 //! ```json
 //! {
 //!    "tagging": {
+//!       "tag_format": "v{major}.{minor}.{patch}{-prerelease}",
+//!       "on_conflict": "abort",
+//!       "exclude": "^(docs|deploy)-",
 //!       "supported_repositories": {
 //!         "github": {
 //!          "enabled": true
@@ -73,6 +106,56 @@
 //! }
 //! ```
 //! 
+//! `tag_format` is used to name new tags and to recognise existing ones. Without it, the format follows the existing tags:
+//! `v{version}` when the highest version tag starts with `v`, as the tags of earlier releases of this tool do, and `{version}` otherwise.
+//! Placeholders: `{version}`, `{major}`, `{minor}`, `{patch}`, `{prerelease}`, `{-prerelease}`, `{build}` and `{+build}`.
+//! Before tagging, the planned tags are checked against all local and remote tags; `on_conflict` either aborts the run (`abort`, the default)
+//! or skips releases (`skip`) whose tag already exists or whose version is not higher than the highest existing one.
+//! Dry-runs only check local tags, and an unreachable `origin` is logged as a warning.
+//! `include` and `exclude` are regexes that limit which tags count as previous releases; tags that do not follow `tag_format` are always ignored.
+//! 
+//! Calendar versioning is selected with a `versioning` section; without it, semantic versioning is used.
+//! 
+//! ```json
+//! {
+//!    "versioning": {
+//!       "scheme": "calver",
+//!       "format": "YY.0M.MICRO"
+//!    }
+//! }
+//! ```
+//! 
+//! While the major version is 0, semantic versioning treats breaking changes as minor and features as patch changes, like Cargo does.
+//! Set `"initial_development": false` in the `versioning` section to bump 0.y.z versions like any other, or pass `--graduate` to release 1.0.0.
+//! 
+//! Without any version tags, the first release is bumped from 0.0.0; set `"initial_version": "1.0.0"` at the top level to release that version first instead.
+//! A commit with a `Release-As: 3.0.0` footer is released as that version, as long as it is higher than the previous release;
+//! refused footers are an error, which dry-runs list under `rejected` of their release instead.
+//!
+//! Commits are classified by `rules` in the `commits` section, the highest `priority` first and in order otherwise; the first match decides.
+//! A rule matches on any of `type`, `subject` and `body` regexes and a `footer` token; the `map` of increments to types is appended as plain type rules.
+//! Commits that no rule matches take the `default` increment of the `commits` section.
+//!
+//! ```json
+//! {
+//!    "commits": {
+//!       "rules": [
+//!          { "increment": "MINOR", "type": "fix", "subject": "\\(security\\)", "priority": 5 },
+//!          { "increment": "MAJOR", "footer": "Security" }
+//!       ]
+//!    }
+//! }
+//! ```
+//!
+//! The `NONE` increment, e.g. `"NONE": ["docs", "ci", "test"]` in the `map`, never bumps the version or releases on its own;
+//! set `"list_none": true` in the `commits` section to list such commits under the other changes of the next release.
+//! With `"default": "NONE"`, only commits that a rule matches can release.
+//!
+//! Commits marked with `[skip release]`, `[no version]` or a `Release: skip` trailer are left out and listed under `skipped` of their release,
+//! of the last one when no later commit releases, or of a pending `none` entry when nothing releases,
+//! and a `[skip ci]` or `[ci skip]` marker on HEAD, or `--to`, makes the whole run a no-op that reports the marker.
+//! Both lists are replaced with `"skip": { "commit": [...], "run": [...] }` in the `commits` section; markers ignore case.
+//!
 //! ## License
 //! 
 //! MIT
@@ -84,7 +167,7 @@ use log::{debug, error, info};
 mod libs;
 mod feature;
 
-use libs::data::*;
+use libs::{data::*, release::ReleaseType};
 use maplit::hashmap;
 
 #[derive(Parser, Debug, Default)]
@@ -124,6 +207,39 @@ struct Args {
 
     #[arg(short, long, help = "Path to the credentials file. Default will go to your {HOME}/.ssh/Github")]
     credentials: Option<String>,
+
+    #[arg(long, help = "Write the planned releases as JSON to this file, or to stdout with `-`.")]
+    output_json: Option<String>,
+
+    #[arg(long, action, help = "Release 1.0.0 from the latest commit, when still in the initial development (0.y.z).", default_value = "false")]
+    graduate: bool,
+
+    #[arg(long, help = "Only consider tags with versions matching this requirement, e.g. \">=2.0\" or \"1.x\".")]
+    only_tags: Option<String>,
+
+    #[arg(long, help = "Only consider tags matching this regex; overrides `tagging.include`.")]
+    tag_include: Option<String>,
+
+    #[arg(long, help = "Ignore tags matching this regex; overrides `tagging.exclude`.")]
+    tag_exclude: Option<String>,
+
+    #[arg(long, help = "Treat a commit as released with this version instead of looking at the tags, e.g. \"a1b2c3d=2.3.0\".")]
+    baseline: Option<String>,
+
+    #[arg(long, help = "Analyse the commits after this revision instead of after the latest release, e.g. a tag or commit.")]
+    from: Option<String>,
+
+    #[arg(long, help = "Analyse the commits up to this revision instead of HEAD, e.g. a tag or commit.")]
+    to: Option<String>,
+
+    #[arg(long, help = "Name of the branch being released; by default taken from the CI environment or the checked out branch.")]
+    branch: Option<String>,
+
+    #[arg(long, action, help = "Fetch more history into shallow clones until the latest release is found, instead of failing.", default_value = "false")]
+    deepen: bool,
+
+    #[arg(long, help = "Check the commit message in this file against Conventional Commits and exit, e.g. from a commit-msg hook; `-` reads stdin.")]
+    lint: Option<String>,
 }
 
 impl Clone for Args
@@ -144,6 +260,17 @@ impl Clone for Args
             keep_minor_up_to_date: self.keep_minor_up_to_date,
             exit_on_error: self.exit_on_error,
             credentials: self.credentials.clone(),
+            only_tags: self.only_tags.clone(),
+            tag_include: self.tag_include.clone(),
+            tag_exclude: self.tag_exclude.clone(),
+            baseline: self.baseline.clone(),
+            from: self.from.clone(),
+            to: self.to.clone(),
+            branch: self.branch.clone(),
+            deepen: self.deepen,
+            lint: self.lint.clone(),
+            graduate: self.graduate,
+            output_json: self.output_json.clone(),
         }
     }
 }
@@ -168,6 +295,25 @@ async fn main() {
     // Parse the command line arguments
     let mut args = Args::parse();
 
+    // Lint a single commit message, which needs neither a configuration nor the repository.
+    if let Some(path) = args.lint.as_deref()
+    {
+        let message = if path == "-" { io::read_to_string(io::stdin()) } else { std::fs::read_to_string(path) };
+        match message.map(|x| libs::conventional::lint(&x))
+        {
+            Ok(Ok(commit)) => info!("Conventional Commit: {}", commit.to_string().lines().next().unwrap_or_default()),
+            Ok(Err(error)) => {
+                error!("Not a Conventional Commit: {}", error);
+                std::process::exit(1);
+            }
+            Err(error) => {
+                error!("Lint: `{}`: {}", path, error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Check if the JSON file path is provided
     let json_file: String = if let Some(json_input) = args.input_file.clone() {
         if json_input.is_empty() {
@@ -192,8 +338,27 @@ async fn main() {
     let data: serde_json::Value = serde_json::from_reader(reader).unwrap();
 
     // Parse the JSON data into SemverData
-    let semver_data: SemverData = serde_json::from_value(data).unwrap();
+    let mut semver_data: SemverData = serde_json::from_value(data).unwrap();
     info!("Read Semantic Version Data");
+
+    // Command line tag filters take precedence over the configuration.
+    if args.tag_include.is_some()
+    {
+        semver_data.tagging.include = args.tag_include.clone();
+    }
+    if args.tag_exclude.is_some()
+    {
+        semver_data.tagging.exclude = args.tag_exclude.clone();
+    }
+
+    let scheme = match semver_data.scheme()
+    {
+        Ok(scheme) => scheme,
+        Err(error) => {
+            error!("{}", error);
+            std::process::exit(1);
+        }
+    };
     // Check if the repository is provided
     let repository_base_path: String = if let Some(repository_base_path) = args.repository.clone() 
     {
@@ -231,14 +396,82 @@ async fn main() {
         std::process::exit(1);
     }
 
+    // Without a configured format, new tags follow the existing ones.
+    let tag_format = match semver_data.tagging.tag_format(scheme, &feature::retrieval::tag_names(&repository))
+    {
+        Ok(tag_format) => tag_format,
+        Err(error) => {
+            error!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    info!("Tag Format: {}", tag_format.template());
+
+    // A run marker on the analysed commit, e.g. `[skip ci]`, releases nothing.
+    let skipped_by = feature::retrieval::run_skip_marker(&args, &semver_data, &repository);
+
     let releases = feature::retrieval::get(
         args.clone(), 
         &semver_data, 
+        &tag_format,
         &repository
     );
 
     info!("Releases: {}", releases.len());
 
+    // Never overwrite existing tags or go backwards.
+    let branch = feature::retrieval::branch(args.branch.as_deref(), &repository);
+    let branch_range = match feature::retrieval::branch_rules(&semver_data, &branch).map(|x| x.range()).transpose()
+    {
+        Ok(branch_range) => branch_range.flatten(),
+        Err(error) => {
+            error!("Branch range: {}", error);
+            std::process::exit(1);
+        }
+    };
+    let on_conflict = semver_data.tagging.on_conflict.unwrap_or_default();
+    let releases = if !feature::tagging::checks_tags(&args)
+    {
+        info!("Dry-run over a range, the releases are not checked against the existing tags");
+        releases
+    }
+    else
+    {
+        match feature::tagging::preflight(releases, &tag_format, branch_range.as_ref(), on_conflict, !args.dry_run, &repository)
+        {
+            Ok(releases) => releases,
+            Err(conflicts) => {
+                error!("Refusing to tag, conflicting tags:");
+                for conflict in conflicts.iter()
+                {
+                    error!("\t{}", conflict);
+                }
+                std::process::exit(1);
+            }
+        }
+    };
+
+    // Hand the release plan to other pipeline steps.
+    if let Some(output_json) = args.output_json.clone()
+    {
+        let json = serde_json::to_string_pretty(&releases).unwrap();
+        if output_json == "-"
+        {
+            println!("{}", json);
+        }
+        else if let Err(error) = std::fs::write(&output_json, json)
+        {
+            error!("Failed to write JSON Output: {} - {}", output_json, error);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(marker) = skipped_by
+    {
+        info!("Skipped by {}: nothing to release", marker);
+        return;
+    }
+
     let repository_types = hashmap! {
         "github.com" => "github",
         // "gitlab.com" => "gitlab",
@@ -268,12 +501,12 @@ async fn main() {
     debug!("Repository Type: {} - {}", repository_type.clone().unwrap(), remote_url);
 
     // Tag the commits
-    for release in releases.iter()
+    for release in releases.iter().filter(|x| x.tag != ReleaseType::None)
     {
         let commit = repository.find_commit(release.commit).unwrap();
 
         // Tag the release commits.
-        if let Some(tag) = feature::tagging::tag(args.clone(), release, &commit, &repository)
+        if let Some(tag) = feature::tagging::tag(args.clone(), release, &tag_format, &commit, &repository)
         {
             // Publish a release to the appropriate repository.
             if semver_data.tagging.supported_repositories.contains_key(repository_type.clone().unwrap().as_str())
@@ -303,22 +536,20 @@ async fn main() {
 /// This function is used to handle the Git Credentials Callback for the Git2 library.
 /// 
 /// ```rust
-/// fn main() {
-///     let user = "git";
-///     let user_from_url = Some("git");
-///     let cred = git2::CredentialType::USERNAME;
+/// let user = "git";
+/// let user_from_url = Some("git");
+/// let cred = git2::CredentialType::USERNAME;
 /// 
-///     let result = crate::git_credentials_callback(user, user_from_url, cred);
+/// let result = crate::git_credentials_callback(user, user_from_url, cred);
 /// 
-///     match result {
-///         Ok(cred) => {
-///             println!("Cred: {:?}", cred);
-///         },
-///         Err(error) => {
-///             println!("Error: {:?}", error);
-///         }
+/// match result {
+///     Ok(cred) => {
+///         println!("Cred: {:?}", cred);
+///     },
+///     Err(error) => {
+///         println!("Error: {:?}", error);
 ///     }
-///}
+/// }
 /// ```
 pub(crate) fn git_credentials_callback(
     _user: &str,
@@ -407,11 +638,7 @@ pub(crate) fn git_credentials_callback(
                 debug!("Authenticate with user {} and private key in memory", user);
     
                 // Check if the public key exists.
-                let public_key = std::env::var("GIT_SSH_KEY_PUBLIC");
-                let public_key = match public_key {
-                    Ok(public_key) => Some(public_key),
-                    _ => None,
-                };
+                let public_key = std::env::var("GIT_SSH_KEY_PUBLIC").ok();
                 let public_key = public_key.as_deref();
                 
                 git2::Cred::ssh_key_from_memory(user, public_key, &private_key, None)